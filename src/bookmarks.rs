@@ -0,0 +1,303 @@
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::{fs, io};
+
+use crate::notifications::Notification;
+use crate::prelude::*;
+use crate::xdg;
+
+/// in `$XDG_CONFIG_HOME/corvus`, written like GTK bookmarks: `file:///path name` per line
+const BOOKMARKS_FILE: &str = "bookmarks";
+
+/// in `$XDG_CONFIG_HOME`
+const GTK_BOOKMARKS_FILE: &str = "gtk-3.0/bookmarks";
+
+const FILE_URI_PREFIX: &str = "file://";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bookmark {
+    pub path: PathBuf,
+    /// shown instead of the file name
+    pub name: Option<String>,
+}
+
+impl Bookmark {
+    pub fn label(&self) -> Cow<'_, str> {
+        match (&self.name, self.path.file_name()) {
+            (Some(name), _) => name.into(),
+            (None, Some(file_name)) => file_name.to_string_lossy(),
+            (None, None) => self.path.to_string_lossy(),
+        }
+    }
+
+    /// `file:///path name`, other URIs like `sftp://` are skipped
+    fn parse(line: &str) -> Option<Self> {
+        let (uri, name) = match line.trim().split_once(' ') {
+            Some((uri, name)) => (uri, Some(name.trim().to_owned())),
+            None => (line.trim(), None),
+        };
+        let path = uri.strip_prefix(FILE_URI_PREFIX)?;
+        Some(Self {
+            path: PathBuf::from(OsString::from_vec(percent_decode(path))),
+            name: name.filter(|name| !name.is_empty()),
+        })
+    }
+
+    fn to_line(&self) -> String {
+        let uri = format!(
+            "{FILE_URI_PREFIX}{}",
+            percent_encode(self.path.as_os_str().as_bytes())
+        );
+        match &self.name {
+            Some(name) => format!("{uri} {name}\n"),
+            None => format!("{uri}\n"),
+        }
+    }
+}
+
+fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+fn percent_encode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Directories to go back to, in the order they are listed
+#[derive(Debug, Default, Resource)]
+pub struct Bookmarks(Vec<Bookmark>);
+
+impl Bookmarks {
+    pub fn iter(&self) -> impl Iterator<Item = &Bookmark> {
+        self.0.iter()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn contains(&self, path: &Path) -> bool {
+        self.0.iter().any(|bookmark| bookmark.path == path)
+    }
+    /// Returns false if `path` is already bookmarked
+    pub fn add(&mut self, path: PathBuf, name: Option<String>) -> bool {
+        if self.contains(&path) {
+            return false;
+        }
+        self.0.push(Bookmark { path, name });
+        true
+    }
+    pub fn remove(&mut self, path: &Path) -> Option<Bookmark> {
+        let index = self.0.iter().position(|bookmark| bookmark.path == path)?;
+        Some(self.0.remove(index))
+    }
+    /// An empty `name` goes back to the file name
+    pub fn rename(&mut self, path: &Path, name: &str) {
+        if let Some(bookmark) = self.0.iter_mut().find(|bookmark| bookmark.path == path) {
+            let name = name.trim();
+            bookmark.name = (!name.is_empty()).then(|| name.to_owned());
+        }
+    }
+    /// Moves the bookmark `step` places up or down the list
+    pub fn move_bookmark(&mut self, path: &Path, step: isize) {
+        let Some(index) = self.0.iter().position(|bookmark| bookmark.path == path) else {
+            return;
+        };
+        let to = index
+            .saturating_add_signed(step)
+            .min(self.0.len().saturating_sub(1));
+        let bookmark = self.0.remove(index);
+        self.0.insert(to, bookmark);
+    }
+    /// Adds the bookmarks of `text` that are missing, returns how many
+    fn merge(&mut self, text: &str) -> usize {
+        text.lines()
+            .filter_map(Bookmark::parse)
+            .filter(|bookmark| self.add(bookmark.path.clone(), bookmark.name.clone()))
+            .count()
+    }
+}
+
+fn load_bookmarks(mut bookmarks: ResMut<Bookmarks>) {
+    let Some(path) = xdg::config_dir().map(|dir| dir.join(BOOKMARKS_FILE)) else {
+        return;
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => {
+            bookmarks.merge(&text);
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => warn!("could not read {path:?}: {e}"),
+    }
+}
+
+fn save_bookmarks(bookmarks: Res<Bookmarks>, mut notifications: EventWriter<Notification>) {
+    let Some(dir) = xdg::config_dir() else {
+        return;
+    };
+    let text = bookmarks.iter().map(Bookmark::to_line).collect::<String>();
+    if let Err(e) = fs::create_dir_all(&dir).and_then(|_| fs::write(dir.join(BOOKMARKS_FILE), text))
+    {
+        notifications.write(Notification::warning(format!(
+            "Cannot save the bookmarks: {e}"
+        )));
+    }
+}
+
+/// Adds the bookmarks of GTK file choosers and file managers
+pub fn import_gtk_bookmarks(
+    mut bookmarks: ResMut<Bookmarks>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(path) = xdg::config_home().map(|dir| dir.join(GTK_BOOKMARKS_FILE)) else {
+        return;
+    };
+    let notification = match fs::read_to_string(&path) {
+        Ok(text) => match bookmarks.merge(&text) {
+            0 => Notification::info("No new GTK bookmarks"),
+            1 => Notification::info("Imported 1 GTK bookmark"),
+            count => Notification::info(format!("Imported {count} GTK bookmarks")),
+        },
+        Err(e) => Notification::warning(format!("Cannot read {}: {e}", path.display())),
+    };
+    notifications.write(notification);
+}
+
+pub fn bookmarks_plugin(app: &mut App) {
+    app.init_resource::<Bookmarks>()
+        .add_systems(Startup, load_bookmarks)
+        .add_systems(
+            Update,
+            // not the loading itself, so an unreadable file is not overwritten on start
+            save_bookmarks
+                .run_if(resource_changed::<Bookmarks>.and(not(resource_added::<Bookmarks>))),
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(path: &str, name: Option<&str>) -> Bookmark {
+        Bookmark {
+            path: PathBuf::from(path),
+            name: name.map(str::to_owned),
+        }
+    }
+
+    fn paths(bookmarks: &Bookmarks) -> Vec<&str> {
+        bookmarks
+            .iter()
+            .map(|bookmark| bookmark.path.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parse_names_with_spaces() {
+        assert_eq!(
+            Bookmark::parse("file:///home/user/Documents"),
+            Some(bookmark("/home/user/Documents", None))
+        );
+        assert_eq!(
+            Bookmark::parse("file:///home/user/src  My Projects \n"),
+            Some(bookmark("/home/user/src", Some("My Projects")))
+        );
+    }
+
+    #[test]
+    fn parse_skips_other_uris() {
+        assert_eq!(Bookmark::parse("sftp://host/home/user"), None);
+        assert_eq!(Bookmark::parse("smb://host/share Share"), None);
+        assert_eq!(Bookmark::parse(""), None);
+    }
+
+    #[test]
+    fn parse_decodes_escapes() {
+        assert_eq!(
+            Bookmark::parse("file:///home/user/My%20Music%C3%A9 Music"),
+            Some(bookmark("/home/user/My Musicé", Some("Music")))
+        );
+        // not an escape, kept as it is
+        assert_eq!(
+            Bookmark::parse("file:///100%/a%zz"),
+            Some(bookmark("/100%/a%zz", None))
+        );
+    }
+
+    #[test]
+    fn percent_round_trips() {
+        for path in [
+            "/plain/path",
+            "/with spaces/a b",
+            "/ünïcödé/日本",
+            "/100%/a%20b",
+        ] {
+            let encoded = percent_encode(path.as_bytes());
+            assert!(!encoded.contains(' '), "{encoded}");
+            assert_eq!(percent_decode(&encoded), path.as_bytes());
+        }
+        assert_eq!(percent_encode(b"/a b%"), "/a%20b%25");
+    }
+
+    #[test]
+    fn lines_round_trip() {
+        for bookmark in [
+            bookmark("/home/user/a b", Some("Spaced Name")),
+            bookmark("/tmp/ünïcödé", None),
+        ] {
+            assert_eq!(Bookmark::parse(&bookmark.to_line()), Some(bookmark));
+        }
+    }
+
+    #[test]
+    fn merge_adds_missing_bookmarks() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add(PathBuf::from("/a"), None);
+        let text = "file:///a A\nsftp://host/b\nfile:///b\nfile:///c C\nfile:///b\n";
+        assert_eq!(bookmarks.merge(text), 2);
+        assert_eq!(paths(&bookmarks), ["/a", "/b", "/c"]);
+        // already there, the name is not replaced
+        assert_eq!(bookmarks.iter().next().unwrap().name, None);
+        assert_eq!(bookmarks.merge(text), 0);
+    }
+
+    #[test]
+    fn move_bookmark_stays_in_the_list() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.merge("file:///a\nfile:///b\nfile:///c\n");
+        bookmarks.move_bookmark(Path::new("/a"), 1);
+        assert_eq!(paths(&bookmarks), ["/b", "/a", "/c"]);
+        bookmarks.move_bookmark(Path::new("/a"), -5);
+        assert_eq!(paths(&bookmarks), ["/a", "/b", "/c"]);
+        bookmarks.move_bookmark(Path::new("/b"), 5);
+        assert_eq!(paths(&bookmarks), ["/a", "/c", "/b"]);
+        bookmarks.move_bookmark(Path::new("/missing"), 1);
+        assert_eq!(paths(&bookmarks), ["/a", "/c", "/b"]);
+    }
+}
@@ -1,19 +1,42 @@
 use crate::fs::{FsCommand, FsEvent};
+use crate::navigation::Navigation;
+use crate::notifications::Notification;
 use crate::prelude::*;
 use crate::ui::ExplorerCommand;
 
-#[derive(Clone, Debug, Deref, DerefMut, Event)]
-pub struct DirectoryChangeRequest(PathBuf);
+/// Changes the directory of the active tab, its history is updated once that worked
+#[derive(Clone, Debug, Event)]
+pub struct DirectoryChangeRequest {
+    pub path: PathBuf,
+    pub navigation: Navigation,
+}
+
+impl DirectoryChangeRequest {
+    /// Goes `steps` entries back or forward in the history, to `path`
+    pub fn jump(path: impl Into<PathBuf>, steps: isize) -> Self {
+        Self {
+            path: path.into(),
+            navigation: Navigation::Jump(steps),
+        }
+    }
+}
 
 impl<P: Into<PathBuf>> From<P> for DirectoryChangeRequest {
     fn from(value: P) -> Self {
-        Self(value.into())
+        Self {
+            path: value.into(),
+            navigation: Navigation::Visit,
+        }
     }
 }
 
 #[derive(Clone, Copy, Debug, Default, Event)]
 pub struct CurrentDirectoryChanged;
 
+/// Another explorer tab was activated, or the first one was opened
+#[derive(Clone, Copy, Debug, Default, Event)]
+pub struct ActiveTabChanged;
+
 fn forward_directory_changed(
     mut fs_events: EventReader<FsEvent>,
     mut current_directory_changed: EventWriter<CurrentDirectoryChanged>,
@@ -40,12 +63,32 @@ fn forward_not_a_directory(mut commands: Commands, mut fs_events: EventReader<Fs
     }
 }
 
+fn forward_fs_errors(
+    mut fs_events: EventReader<FsEvent>,
+    mut notifications: EventWriter<Notification>,
+) {
+    for event in fs_events.read() {
+        match event {
+            FsEvent::IoError(message) => {
+                notifications.write(Notification::error(message.clone()));
+            }
+            FsEvent::OperationDone(message) => {
+                notifications.write(Notification::info(message.clone()));
+            }
+            FsEvent::OperationFailed(message) => {
+                notifications.write(Notification::warning(message.clone()));
+            }
+            _ => {}
+        }
+    }
+}
+
 fn forward_directory_change_request(
     mut reader: EventReader<DirectoryChangeRequest>,
     mut writer: EventWriter<FsCommand>,
 ) {
-    for DirectoryChangeRequest(event) in reader.read() {
-        writer.write(FsCommand::ChangeDirectory(event.clone()));
+    for DirectoryChangeRequest { path, navigation } in reader.read() {
+        writer.write(FsCommand::ChangeDirectory(path.clone(), *navigation));
     }
 }
 
@@ -53,12 +96,14 @@ fn forward_directory_change_request(
 pub fn bridge_plugin(app: &mut App) {
     app.add_event::<DirectoryChangeRequest>()
         .add_event::<CurrentDirectoryChanged>()
+        .add_event::<ActiveTabChanged>()
         .add_systems(
             FixedUpdate,
             (
                 forward_directory_change_request,
                 forward_not_a_directory,
                 forward_directory_changed,
+                forward_fs_errors,
             ),
         );
 }
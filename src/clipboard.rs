@@ -0,0 +1,44 @@
+use crate::notifications::Notification;
+use crate::prelude::*;
+
+/// Request to put text on the system clipboard
+#[derive(Clone, Debug, Deref, Event)]
+pub struct CopyToClipboard(pub String);
+
+/// The system clipboard, opened on first use
+///
+/// On X11 the clipboard content is served by this process, so it has to stay alive.
+#[derive(Default)]
+struct SystemClipboard(Option<arboard::Clipboard>);
+
+impl SystemClipboard {
+    fn set_text(&mut self, text: String) -> Result<(), arboard::Error> {
+        let clipboard = match &mut self.0 {
+            Some(clipboard) => clipboard,
+            empty => empty.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)
+    }
+}
+
+fn copy_to_clipboard(
+    mut requests: EventReader<CopyToClipboard>,
+    mut clipboard: NonSendMut<SystemClipboard>,
+    mut notifications: EventWriter<Notification>,
+) {
+    for CopyToClipboard(text) in requests.read() {
+        if let Err(error) = clipboard.set_text(text.clone()) {
+            warn!("clipboard error: {error:?}");
+            notifications.write(Notification::warning(format!("Cannot copy: {error}")));
+        }
+    }
+}
+
+pub fn clipboard_plugin(app: &mut App) {
+    app.add_event::<CopyToClipboard>()
+        .init_non_send_resource::<SystemClipboard>()
+        .add_systems(
+            Update,
+            copy_to_clipboard.run_if(on_event::<CopyToClipboard>),
+        );
+}
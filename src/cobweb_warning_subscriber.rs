@@ -1,64 +1,193 @@
+use std::fmt::Debug;
 use std::sync::{Arc, Mutex, mpsc};
 
+use bevy::log::tracing::field::{Field, Visit};
 use bevy::log::tracing::{Level, Subscriber};
 use bevy::log::tracing_subscriber::{Layer, layer};
-use bevy::log::{LogPlugin, tracing};
-use derive_more::From;
+use bevy::log::{BoxedLayer, tracing};
 
 use crate::prelude::*;
 
-#[derive(Debug, Deref, DerefMut, Event, From)]
-struct CobwebWarning {
-    message: String,
+const EXTRACT_MODULE: &str = "bevy_cobweb_ui_core::extract";
+const EXTRACTING_PREFIX: &str = "extracting COB file ";
+
+/// How the `Debug` of cobweb's `CobFile` and `ScenePath` appear in its warnings
+const COB_FILE_DEBUG: (&str, &str) = ("CobFile(\"", "\")");
+const SCENE_PATH_DEBUG: (&str, &str) = ("ScenePath { path: [", "] }");
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub(crate) struct CobwebWarning {
+    pub message: String,
+    /// the `.cob` file the warning originates from, e.g. `cobweb/tabs/main.cob`
+    pub file: Option<String>,
+    /// the scene node being extracted, e.g. `main_tab::header`
+    pub scene: Option<String>,
+}
+
+/// The text between the first `start` and the following `end`
+fn between<'a>(text: &'a str, (start, end): (&str, &str)) -> Option<&'a str> {
+    let (_, rest) = text.split_once(start)?;
+    Some(rest.split_once(end)?.0)
+}
+
+impl From<String> for CobwebWarning {
+    /// Cobweb formats the file and scene path into its messages, like
+    /// `failed extracting loadable "Foo" at ScenePath { path: ["a", "b"] } in CobFile("a.cob"); ...`
+    fn from(message: String) -> Self {
+        let file = between(&message, COB_FILE_DEBUG).map(str::to_string);
+        let scene = between(&message, SCENE_PATH_DEBUG).map(|path| {
+            path.split(", ")
+                .map(|name| name.trim_matches('"'))
+                .join("::")
+        });
+        Self {
+            message,
+            file,
+            scene,
+        }
+    }
 }
 
-struct CobwebWarningLayer(mpsc::Sender<CobwebWarning>);
+#[derive(Default)]
+struct MessageVisitor(Option<String>);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+/// Warnings that are still relevant, i.e. whose file has not been re-extracted since
+#[derive(Debug, Default, Deref, Resource)]
+pub(crate) struct CobwebWarnings(Vec<CobwebWarning>);
+
+impl CobwebWarnings {
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+enum CobwebRecord {
+    /// a file is about to be (re-)extracted, e.g. after hot reload
+    Extracting(String),
+    Warning(CobwebWarning),
+}
+
+struct CobwebWarningLayer(mpsc::Sender<CobwebRecord>);
 
 impl<S: Subscriber> Layer<S> for CobwebWarningLayer {
     fn on_event(&self, event: &tracing::Event<'_>, _ctx: layer::Context<'_, S>) {
         let meta = event.metadata();
-        if meta.level() == &Level::WARN
-            && meta
-                .module_path()
-                .unwrap_or_else(|| meta.target())
-                .contains("bevy_cobweb_ui_core::extract")
-            && let Some(field) = event.fields().find(|f| f.name() == "message")
+        if !meta
+            .module_path()
+            .unwrap_or_else(|| meta.target())
+            .contains(EXTRACT_MODULE)
         {
-            self.0
-                .send(CobwebWarning::from(field.to_string()))
-                .expect("Failed to send CobwebWarning");
+            return;
         }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let record = match *meta.level() {
+            Level::WARN => {
+                CobwebRecord::Warning(CobwebWarning::from(visitor.0.unwrap_or_default()))
+            }
+            Level::INFO => match visitor
+                .0
+                .as_deref()
+                .and_then(|message| message.strip_prefix(EXTRACTING_PREFIX))
+            {
+                Some(file) => CobwebRecord::Extracting(file.trim_matches('"').to_string()),
+                None => return,
+            },
+            _ => return,
+        };
+        // the receiver is gone while the app shuts down
+        let _ = self.0.send(record);
     }
 }
 
 #[derive(Debug, Deref, DerefMut, Resource)]
-struct CobwebWarningReceiver(Arc<Mutex<mpsc::Receiver<CobwebWarning>>>);
+struct CobwebRecordReceiver(Arc<Mutex<mpsc::Receiver<CobwebRecord>>>);
+
+impl From<mpsc::Receiver<CobwebRecord>> for CobwebRecordReceiver {
+    fn from(receiver: mpsc::Receiver<CobwebRecord>) -> Self {
+        CobwebRecordReceiver(Arc::new(Mutex::new(receiver)))
+    }
+}
 
-impl From<mpsc::Receiver<CobwebWarning>> for CobwebWarningReceiver {
-    fn from(receiver: mpsc::Receiver<CobwebWarning>) -> Self {
-        CobwebWarningReceiver(Arc::new(Mutex::new(receiver)))
+fn receive_cobweb_records(
+    receiver: Res<CobwebRecordReceiver>,
+    mut warnings: ResMut<CobwebWarnings>,
+    mut writer: EventWriter<CobwebWarning>,
+) {
+    let Ok(lock) = receiver.try_lock() else {
+        return;
+    };
+    // records arrive in the order they were logged, so a re-extraction
+    // clears the old warnings before the new ones come in
+    for record in lock.try_iter() {
+        match record {
+            CobwebRecord::Extracting(file) => {
+                if warnings.iter().any(|w| w.file.as_ref() == Some(&file)) {
+                    warnings.0.retain(|w| w.file.as_ref() != Some(&file));
+                }
+            }
+            CobwebRecord::Warning(warning) => {
+                if !warnings.contains(&warning) {
+                    warnings.0.push(warning.clone());
+                }
+                writer.write(warning);
+            }
+        }
     }
 }
 
-pub fn get_log_plugin() -> LogPlugin {
-    // in debug mode we forward cobweb's warnings to the app
-    LogPlugin {
-        custom_layer: |app| {
-            let (sender, receiver) = mpsc::channel();
-            app.add_event::<CobwebWarning>()
-                .insert_resource(CobwebWarningReceiver::from(receiver))
-                .add_systems(
-                    Update,
-                    |mut writer: EventWriter<CobwebWarning>, cwr: Res<CobwebWarningReceiver>| {
-                        if let Ok(lock) = cwr.try_lock()
-                            && let Ok(warning) = lock.try_recv()
-                        {
-                            writer.write(warning);
-                        }
-                    },
-                );
-            Some(CobwebWarningLayer(sender).boxed())
-        },
-        ..default()
+pub fn cobweb_warning_layer(app: &mut App) -> BoxedLayer {
+    let (sender, receiver) = mpsc::channel();
+    app.add_event::<CobwebWarning>()
+        .init_resource::<CobwebWarnings>()
+        .insert_resource(CobwebRecordReceiver::from(receiver))
+        .add_systems(Update, receive_cobweb_records);
+    CobwebWarningLayer(sender).boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_file_and_scene_of_loadables() {
+        let warning = CobwebWarning::from(
+            r#"failed extracting loadable "BackgroundColor" at ScenePath { path: ["main_tab", "header", "title"] } in CobFile("cobweb/tabs/main.cob"); error resolving defs: "constant $accent not found""#
+                .to_string(),
+        );
+        assert_eq!(warning.file.as_deref(), Some("cobweb/tabs/main.cob"));
+        assert_eq!(warning.scene.as_deref(), Some("main_tab::header::title"));
+    }
+
+    #[test]
+    fn locates_file_of_scene_layers_and_commands() {
+        let warning = CobwebWarning::from(
+            r#"failed extracting scene layer "row" at ScenePath { path: ["tree_row"] } in File(CobFile("cobweb/tabs/main.cob")); error resolving defs: "macro not found""#
+                .to_string(),
+        );
+        assert_eq!(warning.file.as_deref(), Some("cobweb/tabs/main.cob"));
+        assert_eq!(warning.scene.as_deref(), Some("tree_row"));
+
+        let warning = CobwebWarning::from(
+            r#"failed extracting command "LoadFonts" in CobFile("cobweb/main.cob"); error resolving defs: "constant not found""#
+                .to_string(),
+        );
+        assert_eq!(warning.file.as_deref(), Some("cobweb/main.cob"));
+        assert_eq!(warning.scene, None);
+    }
+
+    #[test]
+    fn message_without_location() {
+        let warning = CobwebWarning::from("failed to load cobweb/main.cob".to_string());
+        assert_eq!(warning.file, None);
+        assert_eq!(warning.scene, None);
     }
 }
@@ -0,0 +1,340 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// A key pressed together with modifiers, e.g. `Ctrl+Shift+P`
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct KeyBinding {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    pub const fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+    pub const fn ctrl(self) -> Self {
+        Self { ctrl: true, ..self }
+    }
+    pub const fn shift(self) -> Self {
+        Self {
+            shift: true,
+            ..self
+        }
+    }
+    pub const fn alt(self) -> Self {
+        Self { alt: true, ..self }
+    }
+    /// Text inputs never see keys pressed with Ctrl or Alt
+    pub fn is_modified(&self) -> bool {
+        self.ctrl || self.alt
+    }
+    /// The key went down this frame, with exactly these modifiers held
+    pub fn just_pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.just_pressed(self.key)
+            && self.ctrl == keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+            && self.shift == keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+            && self.alt == keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        let key = format!("{:?}", self.key);
+        let key = ["Key", "Digit", "Arrow"]
+            .iter()
+            .find_map(|prefix| key.strip_prefix(prefix))
+            .unwrap_or(&key);
+        write!(f, "{key}")
+    }
+}
+
+/// Keys that can be used in a [KeyBinding], besides modifiers
+const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Space,
+    KeyCode::Escape,
+    KeyCode::Backspace,
+    KeyCode::Delete,
+    KeyCode::Insert,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Backslash,
+    KeyCode::Semicolon,
+    KeyCode::Quote,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Backquote,
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeyBindingError {
+    #[error("no key in {0:?}")]
+    MissingKey(String),
+    #[error("unknown key {0:?}")]
+    UnknownKey(String),
+}
+
+/// Parses what [KeyBinding] displays, case-insensitive, e.g. `ctrl+shift+p` or `Alt+Left`
+impl FromStr for KeyBinding {
+    type Err = KeyBindingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key) = s
+            .trim()
+            .rsplit_once('+')
+            .map_or(("", s.trim()), |(modifiers, key)| (modifiers, key.trim()));
+        if key.is_empty() {
+            return Err(KeyBindingError::MissingKey(s.to_owned()));
+        }
+        let key = BINDABLE_KEYS
+            .iter()
+            .copied()
+            .find(|k| KeyBinding::new(*k).to_string().eq_ignore_ascii_case(key))
+            .ok_or_else(|| KeyBindingError::UnknownKey(key.to_owned()))?;
+        let mut binding = KeyBinding::new(key);
+        for modifier in modifiers
+            .split('+')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+        {
+            binding = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => binding.ctrl(),
+                "shift" => binding.shift(),
+                "alt" => binding.alt(),
+                _ => return Err(KeyBindingError::UnknownKey(modifier.to_owned())),
+            };
+        }
+        Ok(binding)
+    }
+}
+
+type CommandAction = Arc<dyn Fn(&mut Commands, &str) + Send + Sync>;
+
+/// Something the user can run by name from the command palette, or with its [KeyBinding]
+#[derive(Clone)]
+pub struct RegisteredCommand {
+    pub name: String,
+    pub key_binding: Option<KeyBinding>,
+    /// restored when the keymap is reloaded
+    pub default_key_binding: Option<KeyBinding>,
+    /// prompt for the argument the user has to enter before the command runs
+    pub argument: Option<String>,
+    action: CommandAction,
+}
+
+impl fmt::Debug for RegisteredCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegisteredCommand")
+            .field("name", &self.name)
+            .field("key_binding", &self.key_binding)
+            .field("default_key_binding", &self.default_key_binding)
+            .field("argument", &self.argument)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RegisteredCommand {
+    pub fn new<F>(name: impl Into<String>, action: F) -> Self
+    where
+        F: Fn(&mut Commands) + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            key_binding: None,
+            default_key_binding: None,
+            argument: None,
+            action: Arc::new(move |commands, _| action(commands)),
+        }
+    }
+    pub fn with_argument<F>(name: impl Into<String>, prompt: impl Into<String>, action: F) -> Self
+    where
+        F: Fn(&mut Commands, &str) + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            key_binding: None,
+            default_key_binding: None,
+            argument: Some(prompt.into()),
+            action: Arc::new(action),
+        }
+    }
+    /// Broadcasts `value`, like the buttons do
+    pub fn broadcast<T: Clone + Send + Sync + 'static>(name: impl Into<String>, value: T) -> Self {
+        Self::new(name, move |commands| {
+            commands.react().broadcast(value.clone());
+        })
+    }
+    pub fn send_event<E: Event + Clone>(name: impl Into<String>, event: E) -> Self {
+        Self::new(name, move |commands| {
+            commands.send_event(event.clone());
+        })
+    }
+    /// The default binding, the keymap file can change it
+    pub fn bind(self, key_binding: KeyBinding) -> Self {
+        Self {
+            key_binding: Some(key_binding),
+            default_key_binding: Some(key_binding),
+            ..self
+        }
+    }
+    /// `argument` is ignored by commands without one
+    pub fn run(&self, commands: &mut Commands, argument: &str) {
+        info!("running command {:?}", self.name);
+        (self.action)(commands, argument);
+    }
+}
+
+/// All commands, in the order they were registered
+#[derive(Debug, Default, Deref, DerefMut, Resource)]
+pub struct CommandRegistry(Vec<RegisteredCommand>);
+
+impl CommandRegistry {
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut RegisteredCommand> {
+        self.iter_mut().find(|c| c.name == name)
+    }
+}
+
+pub trait AddCommandExt {
+    fn add_command(&mut self, command: RegisteredCommand) -> &mut Self;
+}
+
+impl AddCommandExt for App {
+    fn add_command(&mut self, command: RegisteredCommand) -> &mut Self {
+        let mut registry = self.world_mut().get_resource_or_init::<CommandRegistry>();
+        if let Some(existing) = registry.iter().find(|c| c.name == command.name) {
+            warn!("command {:?} is registered twice", existing.name);
+        }
+        registry.push(command);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modifiers_in_any_case_and_order() {
+        let binding = KeyBinding::new(KeyCode::KeyP).ctrl().shift();
+        assert_eq!("Ctrl+Shift+P".parse::<KeyBinding>().unwrap(), binding);
+        assert_eq!(
+            "shift + control + p".parse::<KeyBinding>().unwrap(),
+            binding
+        );
+        assert_eq!(
+            "Alt+Left".parse::<KeyBinding>().unwrap(),
+            KeyBinding::new(KeyCode::ArrowLeft).alt()
+        );
+        assert_eq!(
+            "f5".parse::<KeyBinding>().unwrap(),
+            KeyBinding::new(KeyCode::F5)
+        );
+    }
+
+    #[test]
+    fn parses_what_it_displays() {
+        for key in BINDABLE_KEYS {
+            let binding = KeyBinding::new(*key).ctrl().alt();
+            assert_eq!(binding.to_string().parse::<KeyBinding>().unwrap(), binding);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_and_missing_keys() {
+        assert!(matches!(
+            "Ctrl+Nope".parse::<KeyBinding>(),
+            Err(KeyBindingError::UnknownKey(key)) if key == "Nope"
+        ));
+        assert!(matches!(
+            "Super+A".parse::<KeyBinding>(),
+            Err(KeyBindingError::UnknownKey(key)) if key == "Super"
+        ));
+        assert!(matches!(
+            "Ctrl+".parse::<KeyBinding>(),
+            Err(KeyBindingError::MissingKey(_))
+        ));
+        assert!(matches!(
+            "".parse::<KeyBinding>(),
+            Err(KeyBindingError::MissingKey(_))
+        ));
+    }
+}
@@ -7,6 +7,14 @@ pub struct NavigationIconConfig {
     pub next: SmolStr,
     pub up: SmolStr,
     pub reload: SmolStr,
+    pub bookmark: SmolStr,
+    pub home: SmolStr,
+    pub root: SmolStr,
+    pub places: SmolStr,
+    pub location_text: SmolStr,
+    pub breadcrumbs: SmolStr,
+    pub grid: SmolStr,
+    pub columns: SmolStr,
 }
 
 #[derive(Clone, Debug)]
@@ -35,6 +43,14 @@ cfg_if! {
                 next: '🔜',
                 up: '🔝',
                 reload: '🔄',
+                bookmark: '⭐',
+                home: '🏠',
+                root: '💽',
+                places: '📍',
+                location_text: '📝',
+                breadcrumbs: '🍞',
+                grid: '🔲',
+                columns: '📊',
             },
             fs: FsIconConfig {
                 file: '📄',
@@ -50,6 +66,14 @@ cfg_if! {
                 next: s("[N]"),
                 up: s("[U]"),
                 reload: s("[R]"),
+                bookmark: s("[*]"),
+                home: s("[H]"),
+                root: s("[/]"),
+                places: s("[P]"),
+                location_text: s("[T]"),
+                breadcrumbs: s("[>]"),
+                grid: s("[G]"),
+                columns: s("[C]"),
             },
             fs: FsIconConfig {
                 file: s("[F]"),
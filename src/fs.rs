@@ -5,10 +5,13 @@ use std::io::{self, ErrorKind};
 use bevy::tasks::{IoTaskPool, Task, block_on, poll_once};
 use smol_str::SmolStr;
 
-use crate::bridge::CurrentDirectoryChanged;
+use crate::bridge::{ActiveTabChanged, CurrentDirectoryChanged};
 use crate::config::ICON_CONFIG;
+use crate::navigation::{LocationHistory, Navigation};
+use crate::places::expand_home;
 use crate::prelude::{Event, *};
-use crate::resources::{CurrentDirectory, DirectoryEntries, LocationHistory};
+use crate::resources::{CurrentDirectory, DirectoryEntries, DirectoryListings, ExplorerTab,
+                       ExplorerTabs};
 use crate::traits::WithUiIcon;
 
 #[derive(Clone, Component, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -63,6 +66,31 @@ struct Loader {
     task: Task<Result<Vec<ResolvedEntry>, String>>,
 }
 
+/// Changes entries on the [IoTaskPool], the task returns what failed
+#[derive(Component, Debug)]
+struct FileOperation {
+    /// reported when nothing failed
+    done: String,
+    task: Task<Vec<String>>,
+}
+
+impl FileOperation {
+    /// Runs `operation` on every path, collecting its errors
+    fn spawn(
+        done: String,
+        paths: Vec<PathBuf>,
+        operation: impl Fn(&Path) -> Result<(), String> + Send + 'static,
+    ) -> Self {
+        let task = IoTaskPool::get().spawn(async move {
+            paths
+                .iter()
+                .filter_map(|path| operation(path).err())
+                .collect()
+        });
+        Self { done, task }
+    }
+}
+
 #[derive(Clone, Copy)]
 enum ConcreteNode {
     File,
@@ -149,28 +177,56 @@ impl Ord for ResolvedEntry {
 pub enum FsEvent {
     DirectoryChanged(PathBuf),
     NotADirectory(PathBuf),
-    DirectoryResolved { path: PathBuf, entity: Entity },
+    DirectoryResolved {
+        path: PathBuf,
+        entity: Entity,
+    },
     IoError(String),
+    /// a requested operation was carried out
+    OperationDone(String),
+    /// a requested operation could not be carried out
+    OperationFailed(String),
 }
 
 #[derive(Clone, Debug, Event)]
 pub enum FsCommand {
-    ChangeDirectory(PathBuf),
+    ChangeDirectory(PathBuf, Navigation),
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// permanently, directories with all their contents
+    Delete(Vec<PathBuf>),
+    /// into the directory `to`, keeping the names
+    Copy {
+        paths: Vec<PathBuf>,
+        to: PathBuf,
+    },
+    /// into the directory `to`, keeping the names
+    Move {
+        paths: Vec<PathBuf>,
+        to: PathBuf,
+    },
+    /// into [DirectoryListings], again when it already is
+    List(PathBuf),
+}
+
+pub(crate) fn resolve_entry(entry: std::fs::DirEntry) -> Option<ResolvedEntry> {
+    resolve_path(entry.path())
 }
 
-fn resolve_entry(entry: std::fs::DirEntry) -> Option<ResolvedEntry> {
-    let path = entry.path();
+pub(crate) fn resolve_path(path: PathBuf) -> Option<ResolvedEntry> {
     let entry_type = if path.is_file() {
         EntryTypeData::File
     } else if path.is_dir() {
         EntryTypeData::Directory
     } else {
-        let link = read_link(path).ok()?;
+        let link = read_link(&path).ok()?;
         EntryTypeData::Link(link.into())
     };
     let data = NodeInfo {
-        name: entry.file_name().to_string_lossy().to_string(),
-        path: entry.path(),
+        name: path.file_name()?.to_string_lossy().to_string(),
+        path,
     };
     Some(ResolvedEntry {
         info: data,
@@ -192,10 +248,30 @@ fn read_dir_task<P: Into<PathBuf>>(path: P) -> Task<Result<Vec<ResolvedEntry>, S
     })
 }
 
-fn startup_fs_plugin(mut commands: Commands, cwd: Res<CurrentDirectory>) {
-    let path: PathBuf = cwd.clone();
-    let task = read_dir_task(&path);
-    commands.spawn(Loader { path, task });
+/// Opens the first tab in the working directory, unless the session brought back tabs
+fn startup_fs_plugin(
+    mut commands: Commands,
+    mut tabs: ResMut<ExplorerTabs>,
+    mut active_tab_changed: EventWriter<ActiveTabChanged>,
+) {
+    if !tabs.tabs().is_empty() {
+        return;
+    }
+    let cwd = current_dir().expect("no current working directory?!");
+    let tab = commands
+        .spawn((ExplorerTab, CurrentDirectory::from(cwd)))
+        .id();
+    tabs.insert(None, tab);
+    active_tab_changed.write_default();
+}
+
+/// Lists the directories of new tabs
+fn load_new_tabs(mut commands: Commands, new_tabs: Query<&CurrentDirectory, Added<ExplorerTab>>) {
+    for cwd in &new_tabs {
+        let path: PathBuf = cwd.to_path_buf();
+        let task = read_dir_task(&path);
+        commands.spawn(Loader { path, task });
+    }
 }
 
 fn log_error_fn(In(result): In<io::Result<()>>) {
@@ -207,6 +283,7 @@ fn log_error_fn(In(result): In<io::Result<()>>) {
 fn poll_loader_tasks(
     mut commands: Commands,
     mut fs_events: EventWriter<FsEvent>,
+    mut listings: ResMut<DirectoryListings>,
     loaders: Query<(Entity, &mut Loader)>,
 ) {
     for (e, mut loader) in loaders {
@@ -228,6 +305,11 @@ fn poll_loader_tasks(
                 }
                 Err(message) => {
                     error!("Loader Error: {message}");
+                    // the task is done, polling it again would panic
+                    commands.entity(e).despawn();
+                    if listings.is_requested(&loader.path) {
+                        listings.resolve(&loader.path, vec![]);
+                    }
                     FsEvent::IoError(message)
                 }
             };
@@ -235,46 +317,100 @@ fn poll_loader_tasks(
         }
     }
 }
+/// Reports finished [FileOperation]s and shows what they changed
+fn poll_file_operations(
+    mut commands: Commands,
+    mut fs_events: EventWriter<FsEvent>,
+    operations: Query<(Entity, &mut FileOperation)>,
+    tabs: Query<&CurrentDirectory, With<ExplorerTab>>,
+    listings: Res<DirectoryListings>,
+) {
+    for (e, mut operation) in operations {
+        let Some(failures) = block_on(poll_once(&mut operation.task)) else {
+            continue;
+        };
+        commands.entity(e).despawn();
+        fs_events.write(match failures.is_empty() {
+            true => FsEvent::OperationDone(operation.done.clone()),
+            false => FsEvent::OperationFailed(failures.join("\n")),
+        });
+        let tab_directories = tabs.iter().map(|cwd| cwd.to_path_buf());
+        reload_directories(&mut commands, tab_directories, &listings);
+    }
+}
+
+/// Every tab showing the loaded directory gets its entries, and so do [DirectoryListings].
+/// The loader is despawned then, it is done
 fn update_directory_entries(
+    mut commands: Commands,
     mut events: EventReader<FsEvent>,
-    cwd: Res<CurrentDirectory>,
-    mut entries: ResMut<DirectoryEntries>,
+    mut tabs: Query<(&CurrentDirectory, &mut DirectoryEntries), With<ExplorerTab>>,
+    mut listings: ResMut<DirectoryListings>,
     loaded_directories: Query<&LoadedDirectory>,
 ) {
     for event in events.read() {
-        if let FsEvent::DirectoryResolved { path, entity } = event.clone()
-            && path == **cwd
-            && let Ok(directory) = loaded_directories.get(entity)
+        let FsEvent::DirectoryResolved { path, entity } = event.clone() else {
+            continue;
+        };
+        commands.entity(entity).despawn();
+        if let Ok(directory) = loaded_directories.get(entity)
             && directory.path == path
         {
-            **entries = directory.entries.clone();
+            for (cwd, mut entries) in &mut tabs {
+                if **cwd == path {
+                    **entries = directory.entries.clone();
+                }
+            }
+            if listings.is_requested(&path) {
+                listings.resolve(&path, directory.entries.clone());
+            }
         }
     }
 }
 
+/// Directory changes apply to the active tab
 fn handle_fs_commands(
     mut commands: Commands,
     mut fs_commands: EventReader<FsCommand>,
-    mut current_directory: ResMut<CurrentDirectory>,
-    mut location_history: ResMut<LocationHistory>,
+    explorer_tabs: Res<ExplorerTabs>,
+    mut tabs: Query<(&mut CurrentDirectory, &mut LocationHistory), With<ExplorerTab>>,
+    mut listings: ResMut<DirectoryListings>,
 ) {
     for command in fs_commands.read() {
         match command {
-            FsCommand::ChangeDirectory(path) => {
+            FsCommand::ChangeDirectory(path, navigation) => {
+                let Some((mut current_directory, mut location_history)) = explorer_tabs
+                    .active()
+                    .and_then(|tab| tabs.get_mut(tab).ok())
+                else {
+                    continue;
+                };
+                // typed paths may start with `~`
+                let path = expand_home(path);
                 let path: PathBuf = if !path.is_absolute() {
                     current_directory.join(path)
                 } else {
-                    path.to_owned()
-                }
-                .canonicalize()
-                .expect("path cannot be canonicalized");
-                if path == **current_directory {
-                    return;
+                    path
+                };
+                let path = match path.canonicalize() {
+                    Ok(path) => path,
+                    Err(e) => {
+                        warn!("SetDirectory({path:?}) ERROR: {e:?}");
+                        commands.send_event(FsEvent::OperationFailed(format!(
+                            "Cannot open {}: {e}",
+                            path.display()
+                        )));
+                        continue;
+                    }
+                };
+                // going through the history to the same directory still moves in it
+                if path == **current_directory && *navigation == Navigation::Visit {
+                    continue;
                 }
                 info!("SetDirectory {path:?}");
                 match env::set_current_dir(&path) {
                     Ok(_) => {
-                        location_history.back.push(current_directory.clone());
+                        location_history.navigate(*navigation, &current_directory);
                         **current_directory = path.clone();
                         let task = read_dir_task(&path);
                         commands.spawn(Loader { path, task });
@@ -285,20 +421,143 @@ fn handle_fs_commands(
                             info!("not a directory, opening as preview");
                             commands.send_event(FsEvent::NotADirectory(path));
                         } else {
-                            warn!("SetDirectory({path:?}) ERROR: {e:?}")
+                            warn!("SetDirectory({path:?}) ERROR: {e:?}");
+                            commands.send_event(FsEvent::OperationFailed(format!(
+                                "Cannot open {}: {e}",
+                                path.display()
+                            )));
                         }
                     }
                 };
             }
+            FsCommand::Rename { from, to } => {
+                info!("rename {from:?} to {to:?}");
+                if to.exists() {
+                    commands.send_event(FsEvent::OperationFailed(format!(
+                        "Cannot rename {}: {} already exists",
+                        from.display(),
+                        to.display()
+                    )));
+                } else if let Err(e) = std::fs::rename(from, to) {
+                    commands.send_event(FsEvent::OperationFailed(format!(
+                        "Cannot rename {}: {e}",
+                        from.display()
+                    )));
+                }
+                let tab_directories = tabs.iter().map(|(cwd, _)| cwd.to_path_buf());
+                reload_directories(&mut commands, tab_directories, &listings);
+            }
+            FsCommand::Delete(paths) => {
+                let done = format!("Deleted {} entries", paths.len());
+                commands.spawn(FileOperation::spawn(done, paths.clone(), |path| {
+                    info!("delete {path:?}");
+                    // do not follow symlinks to directories
+                    let result = if path.is_dir() && !path.is_symlink() {
+                        std::fs::remove_dir_all(path)
+                    } else {
+                        std::fs::remove_file(path)
+                    };
+                    result.map_err(|e| format!("Cannot delete {}: {e}", path.display()))
+                }));
+            }
+            FsCommand::Copy { paths, to } => {
+                let done = format!("Copied {} entries to {}", paths.len(), to.display());
+                let to = to.clone();
+                commands.spawn(FileOperation::spawn(done, paths.clone(), move |path| {
+                    let target = to.join(path.file_name().unwrap_or_default());
+                    info!("copy {path:?} to {target:?}");
+                    copy_entry(path, &target)
+                        .map_err(|e| format!("Cannot copy {}: {e}", path.display()))
+                }));
+            }
+            FsCommand::Move { paths, to } => {
+                let done = format!("Moved {} entries to {}", paths.len(), to.display());
+                let to = to.clone();
+                commands.spawn(FileOperation::spawn(done, paths.clone(), move |path| {
+                    let target = to.join(path.file_name().unwrap_or_default());
+                    info!("move {path:?} to {target:?}");
+                    move_entry(path, &target)
+                        .map_err(|e| format!("Cannot move {}: {e}", path.display()))
+                }));
+            }
+            FsCommand::List(path) => {
+                listings.request(path.clone());
+                let task = read_dir_task(path);
+                commands.spawn(Loader {
+                    path: path.clone(),
+                    task,
+                });
+            }
         }
     }
 }
 
+/// Copies files, links and directories with all their contents, never overwrites
+fn copy_entry(from: &Path, to: &Path) -> io::Result<()> {
+    if to.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} already exists", to.display()),
+        ));
+    }
+    let metadata = from.symlink_metadata()?;
+    if metadata.is_symlink() {
+        std::os::unix::fs::symlink(read_link(from)?, to)
+    } else if metadata.is_dir() {
+        if to.starts_with(from) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "cannot copy a directory into itself",
+            ));
+        }
+        std::fs::create_dir(to)?;
+        for entry in read_dir(from)? {
+            let entry = entry?;
+            copy_entry(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(drop)
+    }
+}
+
+/// Renames, or copies and deletes when `to` is on another file system
+fn move_entry(from: &Path, to: &Path) -> io::Result<()> {
+    if to.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} already exists", to.display()),
+        ));
+    }
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            copy_entry(from, to)?;
+            if from.is_dir() && !from.is_symlink() {
+                std::fs::remove_dir_all(from)
+            } else {
+                std::fs::remove_file(from)
+            }
+        }
+        result => result,
+    }
+}
+
+/// Lists the directories of all tabs and the shown [DirectoryListings] again, after entries were changed
+fn reload_directories(
+    commands: &mut Commands,
+    tab_directories: impl Iterator<Item = PathBuf>,
+    listings: &DirectoryListings,
+) {
+    let listed = listings.paths().map(Path::to_owned);
+    for path in tab_directories.chain(listed).unique() {
+        let task = read_dir_task(&path);
+        commands.spawn(Loader { path, task });
+    }
+}
+
 pub fn fs_plugin(app: &mut App) {
-    app.init_resource::<DirectoryEntries>()
-        .insert_resource(CurrentDirectory::from(
-            current_dir().expect("no current working directory?!"),
-        ))
+    app.init_resource::<ExplorerTabs>()
+        .init_resource::<DirectoryListings>()
         .add_event::<FsEvent>()
         .add_event::<FsCommand>()
         .add_systems(
@@ -309,9 +568,10 @@ pub fn fs_plugin(app: &mut App) {
         .add_systems(
             FixedUpdate,
             (
+                load_new_tabs,
                 poll_loader_tasks,
-                update_directory_entries
-                    .run_if(on_event::<FsEvent>.or(resource_changed::<CurrentDirectory>)),
+                poll_file_operations,
+                update_directory_entries.run_if(on_event::<FsEvent>),
                 handle_fs_commands.run_if(on_event::<FsCommand>),
             ),
         );
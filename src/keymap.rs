@@ -0,0 +1,223 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+use crate::command_registry::{AddCommandExt, CommandRegistry, KeyBinding, RegisteredCommand};
+use crate::notifications::Notification;
+use crate::prelude::*;
+use crate::xdg;
+
+/// Changes key bindings of registered commands, in the config dir:
+/// ```text
+/// # command name = key chord
+/// Go back = Alt+Left
+/// Quick open = Ctrl+O
+/// # remove a default binding
+/// Reload = none
+/// ```
+const KEYMAP_FILE: &str = "keymap.conf";
+
+/// Command names with their new binding, `None` removes it, and the lines that could not be parsed
+fn parse_keymap(text: &str) -> (Vec<(String, Option<KeyBinding>)>, Vec<String>) {
+    let mut bindings = vec![];
+    let mut errors = vec![];
+    for (number, line) in text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+    {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((name, chord)) = line.split_once('=') else {
+            errors.push(format!("line {number}: expected `command name = key`"));
+            continue;
+        };
+        let (name, chord) = (name.trim(), chord.trim());
+        if chord.eq_ignore_ascii_case("none") {
+            bindings.push((name.to_owned(), None));
+            continue;
+        }
+        match chord.parse() {
+            Ok(binding) => bindings.push((name.to_owned(), Some(binding))),
+            Err(e) => errors.push(format!("line {number}: {e}")),
+        }
+    }
+    (bindings, errors)
+}
+
+/// Only one command keeps each binding, preferring the ones set in the keymap file.
+/// Returns a warning for every binding that was removed
+fn resolve_conflicts(registry: &mut CommandRegistry, preferred: &HashSet<String>) -> Vec<String> {
+    let order = (0..registry.len())
+        .sorted_by_key(|i| !preferred.contains(&registry[*i].name))
+        .collect_vec();
+    let mut owners: HashMap<KeyBinding, String> = default();
+    let mut warnings = vec![];
+    for i in order {
+        let command = &mut registry[i];
+        let Some(binding) = command.key_binding else {
+            continue;
+        };
+        if let Some(owner) = owners.get(&binding) {
+            warnings.push(format!(
+                "{binding} is bound to {owner:?} and {:?}, only {owner:?} is used",
+                command.name
+            ));
+            command.key_binding = None;
+        } else {
+            owners.insert(binding, command.name.clone());
+        }
+    }
+    warnings
+}
+
+fn load_keymap(
+    mut registry: ResMut<CommandRegistry>,
+    mut notifications: EventWriter<Notification>,
+) {
+    for command in registry.iter_mut() {
+        command.key_binding = command.default_key_binding;
+    }
+    let mut preferred = HashSet::new();
+    if let Some(path) = xdg::config_dir().map(|dir| dir.join(KEYMAP_FILE)) {
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                info!("loading keymap {path:?}");
+                let (bindings, errors) = parse_keymap(&text);
+                for error in errors {
+                    notifications.write(Notification::warning(format!("{path:?}: {error}")));
+                }
+                for (name, binding) in bindings {
+                    let Some(command) = registry.find_mut(&name) else {
+                        notifications.write(Notification::warning(format!(
+                            "{path:?}: there is no command {name:?}"
+                        )));
+                        continue;
+                    };
+                    command.key_binding = binding;
+                    preferred.insert(name);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                debug!("no keymap at {path:?}, using the defaults");
+            }
+            Err(e) => {
+                notifications.write(Notification::warning(format!(
+                    "could not read {path:?}: {e}"
+                )));
+            }
+        }
+    }
+    for warning in resolve_conflicts(&mut registry, &preferred) {
+        notifications.write(Notification::warning(warning));
+    }
+}
+
+pub fn keymap_plugin(app: &mut App) {
+    app.add_command(RegisteredCommand::new("Reload keymap", |commands| {
+        commands.run_system_cached(load_keymap);
+    }))
+    .add_systems(Startup, load_keymap);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(commands: &[(&str, Option<KeyBinding>)]) -> CommandRegistry {
+        let mut registry = CommandRegistry::default();
+        registry.extend(commands.iter().map(|(name, binding)| {
+            let command = RegisteredCommand::new(*name, |_| {});
+            match binding {
+                Some(binding) => command.bind(*binding),
+                None => command,
+            }
+        }));
+        registry
+    }
+
+    fn bindings(registry: &CommandRegistry) -> Vec<(&str, Option<KeyBinding>)> {
+        registry
+            .iter()
+            .map(|command| (command.name.as_str(), command.key_binding))
+            .collect()
+    }
+
+    #[test]
+    fn parses_bindings_and_unbinding() {
+        let (bindings, errors) = parse_keymap(
+            "# a comment\n\
+             \n\
+             Go back = Alt+Left\n\
+             Quick open=ctrl+shift+o\n\
+             Reload = NONE\n",
+        );
+        assert_eq!(
+            bindings,
+            [
+                (
+                    "Go back".to_string(),
+                    Some(KeyBinding::new(KeyCode::ArrowLeft).alt())
+                ),
+                (
+                    "Quick open".to_string(),
+                    Some(KeyBinding::new(KeyCode::KeyO).ctrl().shift())
+                ),
+                ("Reload".to_string(), None),
+            ]
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn reports_invalid_lines() {
+        let (bindings, errors) = parse_keymap(
+            "Go back\n\
+             Quick open = Ctrl+Nope\n\
+             Reload = Hyper+R\n\
+             Refresh = Ctrl+\n\
+             Search = F3\n",
+        );
+        assert_eq!(
+            bindings,
+            [("Search".to_string(), Some(KeyBinding::new(KeyCode::F3)))]
+        );
+        assert_eq!(
+            errors,
+            [
+                "line 1: expected `command name = key`",
+                "line 2: unknown key \"Nope\"",
+                "line 3: unknown key \"Hyper\"",
+                "line 4: no key in \"Ctrl+\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn preferred_bindings_win_conflicts() {
+        let ctrl_o = KeyBinding::new(KeyCode::KeyO).ctrl();
+        let mut registry = registry(&[
+            ("Open", Some(ctrl_o)),
+            ("Quick open", Some(ctrl_o)),
+            ("Reload", Some(KeyBinding::new(KeyCode::F5))),
+        ]);
+        let warnings = resolve_conflicts(&mut registry, &HashSet::from(["Quick open".to_string()]));
+        assert_eq!(
+            bindings(&registry),
+            [
+                ("Open", None),
+                ("Quick open", Some(ctrl_o)),
+                ("Reload", Some(KeyBinding::new(KeyCode::F5))),
+            ]
+        );
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn first_registered_wins_without_preference() {
+        let f2 = KeyBinding::new(KeyCode::F2);
+        let mut registry = registry(&[("Rename", Some(f2)), ("Other", Some(f2))]);
+        resolve_conflicts(&mut registry, &default());
+        assert_eq!(bindings(&registry), [("Rename", Some(f2)), ("Other", None)]);
+    }
+}
@@ -1,17 +1,43 @@
+use crate::bookmarks::bookmarks_plugin;
 use crate::bridge::bridge_plugin;
+use crate::clipboard::clipboard_plugin;
 use crate::fs::fs_plugin;
+use crate::keymap::keymap_plugin;
+use crate::mime_apps::mime_apps_plugin;
+use crate::notifications::notifications_plugin;
 use crate::prelude::*;
-use crate::resources::{LocationHistory, PreviewPath};
+use crate::recent_locations::recent_locations_plugin;
+use crate::search::search_plugin;
+use crate::session::session_plugin;
+use crate::settings::settings_plugin;
 use crate::ui::ui_plugin;
+use crate::window_settings::window_settings_plugin;
 
+mod bookmarks;
 mod bridge;
+mod clipboard;
 #[cfg(debug_assertions)]
 mod cobweb_warning_subscriber;
+mod command_registry;
 pub mod config;
 mod fs;
+mod keymap;
+mod log_capture;
+mod mime_apps;
+mod navigation;
+mod notifications;
+mod places;
+mod recent_locations;
 mod resources;
+mod search;
+mod session;
+mod settings;
+#[cfg(test)]
+mod test_dir;
 mod traits;
 mod ui;
+mod window_settings;
+mod xdg;
 
 mod prelude {
     pub use std::path::{Path, PathBuf};
@@ -38,14 +64,19 @@ mod prelude {
 ///```
 
 pub fn corvus_plugin(app: &mut App) {
-    cfg_if! {
-        if #[cfg(debug_assertions)] {
-            let log_plugin =cobweb_warning_subscriber:: get_log_plugin();
-        } else {
-            let log_plugin = LogPlugin::default();
-        }
-    }
+    let log_plugin = log_capture::get_log_plugin();
     app.add_plugins(DefaultPlugins.set(log_plugin))
-        .add_plugins(bridge_plugin)
-        .add_plugins((fs_plugin, ui_plugin));
+        .add_plugins((
+            bridge_plugin,
+            clipboard_plugin,
+            keymap_plugin,
+            mime_apps_plugin,
+            notifications_plugin,
+            settings_plugin,
+            bookmarks_plugin,
+            recent_locations_plugin,
+            session_plugin,
+            window_settings_plugin,
+        ))
+        .add_plugins((fs_plugin, search_plugin, ui_plugin));
 }
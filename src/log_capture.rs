@@ -0,0 +1,151 @@
+use std::collections::VecDeque;
+use std::fmt::{Debug, Write};
+use std::sync::{Arc, Mutex, mpsc};
+
+use bevy::log::tracing::field::{Field, Visit};
+use bevy::log::tracing::{Level, Subscriber};
+use bevy::log::tracing_subscriber::{Layer, layer};
+use bevy::log::{BoxedLayer, LogPlugin, tracing};
+use chrono::{DateTime, Local};
+
+use crate::prelude::*;
+
+/// number of records kept around for the log tab
+const LOG_BUFFER_CAPACITY: usize = 5000;
+
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    /// increases with every record, survives clearing the buffer
+    pub id: u64,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    pub timestamp: DateTime<Local>,
+}
+
+impl LogRecord {
+    pub fn time_label(&self) -> String {
+        self.timestamp.format("%H:%M:%S%.3f").to_string()
+    }
+    pub fn to_line(&self) -> String {
+        format!(
+            "{} {:>5} {}: {}",
+            self.timestamp.to_rfc3339(),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// Ring buffer with the most recent log records, oldest first
+#[derive(Debug, Default, Resource)]
+pub struct LogBuffer {
+    records: VecDeque<LogRecord>,
+    next_id: u64,
+}
+
+impl LogBuffer {
+    fn push(&mut self, mut record: LogRecord) {
+        if self.records.len() == LOG_BUFFER_CAPACITY {
+            self.records.pop_front();
+        }
+        record.id = self.next_id;
+        self.next_id += 1;
+        self.records.push_back(record);
+    }
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LogRecord> {
+        self.records.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+    /// id of the most recent record, if any
+    pub fn last_id(&self) -> Option<u64> {
+        self.records.back().map(|record| record.id)
+    }
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+/// Collects the `message` field of an event, followed by any other fields
+#[derive(Default)]
+pub(crate) struct MessageVisitor {
+    pub message: Option<String>,
+    pub fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{value:?}"));
+        } else {
+            _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
+
+struct LogRecordLayer(mpsc::Sender<LogRecord>);
+
+impl<S: Subscriber> Layer<S> for LogRecordLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: layer::Context<'_, S>) {
+        let meta = event.metadata();
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let message = visitor.message.unwrap_or_default() + &visitor.fields;
+        // the receiver is gone while the app shuts down
+        _ = self.0.send(LogRecord {
+            id: 0,
+            level: *meta.level(),
+            target: meta.target().to_string(),
+            message,
+            timestamp: Local::now(),
+        });
+    }
+}
+
+#[derive(Debug, Deref, DerefMut, Resource)]
+struct LogRecordReceiver(Arc<Mutex<mpsc::Receiver<LogRecord>>>);
+
+impl From<mpsc::Receiver<LogRecord>> for LogRecordReceiver {
+    fn from(receiver: mpsc::Receiver<LogRecord>) -> Self {
+        LogRecordReceiver(Arc::new(Mutex::new(receiver)))
+    }
+}
+
+fn receive_log_records(receiver: Res<LogRecordReceiver>, mut buffer: ResMut<LogBuffer>) {
+    let Ok(lock) = receiver.try_lock() else {
+        return;
+    };
+    for record in lock.try_iter() {
+        buffer.push(record);
+    }
+}
+
+fn log_capture_layer(app: &mut App) -> BoxedLayer {
+    let (sender, receiver) = mpsc::channel();
+    app.init_resource::<LogBuffer>()
+        .insert_resource(LogRecordReceiver::from(receiver))
+        .add_systems(Update, receive_log_records);
+    LogRecordLayer(sender).boxed()
+}
+
+/// [LogPlugin] that feeds every log record into the [LogBuffer]
+pub fn get_log_plugin() -> LogPlugin {
+    LogPlugin {
+        custom_layer: |app| {
+            let layer = log_capture_layer(app);
+            cfg_if! {
+                if #[cfg(debug_assertions)] {
+                    // in debug mode we also forward cobweb's warnings to the app
+                    let cobweb_layer = crate::cobweb_warning_subscriber::cobweb_warning_layer(app);
+                    Some(Box::new(vec![layer, cobweb_layer]))
+                } else {
+                    Some(layer)
+                }
+            }
+        },
+        ..default()
+    }
+}
@@ -0,0 +1,585 @@
+use std::collections::{HashMap, HashSet};
+use std::process::{Child, Command as Process};
+use std::sync::{LazyLock, mpsc};
+use std::time::Duration;
+use std::{io, mem, thread};
+
+use crate::command_registry::{AddCommandExt, RegisteredCommand};
+use crate::mime_apps::desktop_entry::DesktopEntry;
+use crate::mime_apps::globs::{MimeGlob, parse_globs2};
+use crate::mime_apps::ini::{parse_ini, split_list};
+use crate::mime_apps::mimeapps_list::with_default;
+use crate::notifications::Notification;
+use crate::prelude::*;
+use crate::xdg;
+
+pub mod desktop_entry;
+mod globs;
+mod ini;
+mod mimeapps_list;
+
+/// associations and defaults, the first file found wins
+const MIMEAPPS_LIST: &str = "mimeapps.list";
+const DEFAULT_APPLICATIONS: &str = "Default Applications";
+const ADDED_ASSOCIATIONS: &str = "Added Associations";
+const REMOVED_ASSOCIATIONS: &str = "Removed Associations";
+
+const DIRECTORY_MIME_TYPE: &str = "inode/directory";
+const TEXT_MIME_TYPE: &str = "text/plain";
+const BINARY_MIME_TYPE: &str = "application/octet-stream";
+
+/// How often exited processes that were started detached are reaped
+const REAP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where to look for mime types, applications and their associations
+#[derive(Clone, Debug, Default)]
+pub struct SearchDirs {
+    pub config_home: Option<PathBuf>,
+    pub config_dirs: Vec<PathBuf>,
+    pub data_home: Option<PathBuf>,
+    pub data_dirs: Vec<PathBuf>,
+}
+
+impl SearchDirs {
+    pub fn from_env() -> Self {
+        Self {
+            config_home: xdg::config_home(),
+            config_dirs: xdg::config_dirs(),
+            data_home: xdg::data_home(),
+            data_dirs: xdg::data_dirs(),
+        }
+    }
+
+    /// in order of preference
+    fn data(&self) -> impl Iterator<Item = &PathBuf> {
+        self.data_home.iter().chain(&self.data_dirs)
+    }
+
+    /// in order of preference
+    fn mimeapps_lists(&self) -> impl Iterator<Item = PathBuf> {
+        self.config_home
+            .iter()
+            .chain(&self.config_dirs)
+            .map(|dir| dir.join(MIMEAPPS_LIST))
+            .chain(
+                self.data()
+                    .map(|dir| dir.join("applications").join(MIMEAPPS_LIST)),
+            )
+    }
+}
+
+/// Children that were started detached, waited for by a single thread
+static REAPER: LazyLock<mpsc::Sender<Child>> = LazyLock::new(|| {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || reap(receiver));
+    sender
+});
+
+fn reap(receiver: mpsc::Receiver<Child>) {
+    let mut children: Vec<Child> = vec![];
+    loop {
+        // nothing to check until a child is started
+        let received = match children.is_empty() {
+            true => receiver.recv().ok(),
+            false => receiver.recv_timeout(REAP_INTERVAL).ok(),
+        };
+        children.extend(received);
+        children.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+    }
+}
+
+/// Starts `process` without waiting for it, it is reaped in the background once it exits
+pub(crate) fn spawn_detached(process: &mut Process) -> io::Result<()> {
+    let child = process.spawn()?;
+    // the reaper never stops, so neither does its receiver
+    let _ = REAPER.send(child);
+    Ok(())
+}
+
+/// Mime types, installed applications and which of them open what
+#[derive(Debug, Default, Resource)]
+pub struct MimeApps {
+    dirs: SearchDirs,
+    /// best match first
+    globs: Vec<MimeGlob>,
+    /// mime type to the types it is a subclass of
+    parents: HashMap<String, Vec<String>>,
+    applications: Vec<DesktopEntry>,
+    defaults: HashMap<String, Vec<String>>,
+    added: HashMap<String, Vec<String>>,
+    removed: HashMap<String, HashSet<String>>,
+}
+
+impl MimeApps {
+    /// Missing or unreadable files are skipped
+    pub fn load(dirs: SearchDirs) -> Self {
+        let mut mime_apps = Self::default();
+        for dir in dirs.data() {
+            let mime_dir = dir.join("mime");
+            if let Ok(text) = std::fs::read_to_string(mime_dir.join("globs2")) {
+                mime_apps.globs.extend(parse_globs2(&text));
+            }
+            if let Ok(text) = std::fs::read_to_string(mime_dir.join("subclasses")) {
+                for (child, parent) in text.lines().filter_map(|line| line.split_once(' ')) {
+                    mime_apps
+                        .parents
+                        .entry(child.to_owned())
+                        .or_default()
+                        .push(parent.to_owned());
+                }
+            }
+        }
+        // longer patterns are more specific, `*.tar.gz` beats `*.gz`
+        mime_apps.globs.sort_by_key(|glob| {
+            (
+                std::cmp::Reverse(glob.weight),
+                std::cmp::Reverse(glob.pattern.len()),
+            )
+        });
+
+        let mut seen = HashSet::new();
+        for dir in dirs.data() {
+            let applications = dir.join("applications");
+            for entry in ignore::WalkBuilder::new(&applications)
+                .standard_filters(false)
+                .sort_by_file_name(|a, b| a.cmp(b))
+                .build()
+                .flatten()
+            {
+                let path = entry.path();
+                if path.extension().is_none_or(|ext| ext != "desktop") {
+                    continue;
+                }
+                let Ok(relative) = path.strip_prefix(&applications) else {
+                    continue;
+                };
+                let id = relative
+                    .to_string_lossy()
+                    .replace(std::path::MAIN_SEPARATOR, "-");
+                // earlier directories override, hidden entries included
+                if !seen.insert(id.clone()) {
+                    continue;
+                }
+                if let Ok(text) = std::fs::read_to_string(path)
+                    && let Some(application) = DesktopEntry::parse(id, path.to_owned(), &text)
+                {
+                    mime_apps.applications.push(application);
+                }
+            }
+        }
+
+        for list in dirs.mimeapps_lists() {
+            let Ok(text) = std::fs::read_to_string(&list) else {
+                continue;
+            };
+            for (group, mime_type, ids) in parse_ini(&text) {
+                let ids = split_list(ids).map(str::to_owned);
+                let mime_type = mime_type.to_owned();
+                match group {
+                    DEFAULT_APPLICATIONS => {
+                        mime_apps.defaults.entry(mime_type).or_default().extend(ids)
+                    }
+                    ADDED_ASSOCIATIONS => mime_apps.added.entry(mime_type).or_default().extend(ids),
+                    REMOVED_ASSOCIATIONS => {
+                        mime_apps.removed.entry(mime_type).or_default().extend(ids)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        mime_apps.dirs = dirs;
+        mime_apps
+    }
+
+    /// From the file name, or the contents if no pattern matches
+    pub fn mime_type(&self, path: &Path) -> String {
+        if path.is_dir() {
+            return DIRECTORY_MIME_TYPE.to_owned();
+        }
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        if let Some(glob) = self.globs.iter().find(|glob| glob.matches(&file_name)) {
+            return glob.mime_type.clone();
+        }
+        let mut head = vec![0; 512];
+        let looks_like_text = std::fs::File::open(path)
+            .and_then(|mut file| io::Read::read(&mut file, &mut head))
+            .is_ok_and(|n| {
+                // a multi byte character may be cut off at the end
+                let head = &head[..n];
+                !head.contains(&0)
+                    && std::str::from_utf8(head).map_or_else(|e| e.error_len().is_none(), |_| true)
+            });
+        if looks_like_text {
+            TEXT_MIME_TYPE.to_owned()
+        } else {
+            BINARY_MIME_TYPE.to_owned()
+        }
+    }
+
+    /// The mime type followed by the types it is a subclass of
+    fn with_parents(&self, mime_type: &str) -> Vec<String> {
+        let mut types = vec![mime_type.to_owned()];
+        let mut i = 0;
+        while let Some(current) = types.get(i).cloned() {
+            let mut parents = self.parents.get(&current).cloned().unwrap_or_default();
+            // all text formats can be opened as text
+            if current.starts_with("text/") {
+                parents.push(TEXT_MIME_TYPE.to_owned());
+            }
+            for parent in parents {
+                if !types.contains(&parent) {
+                    types.push(parent);
+                }
+            }
+            i += 1;
+        }
+        types
+    }
+
+    pub fn application(&self, id: &str) -> Option<&DesktopEntry> {
+        self.applications
+            .iter()
+            .find(|application| application.id == id)
+    }
+
+    /// Applications for exactly this type, without the defaults
+    fn associated(&self, mime_type: &str) -> impl Iterator<Item = &DesktopEntry> {
+        let removed = self.removed.get(mime_type);
+        let added = self.added.get(mime_type).into_iter().flatten();
+        added
+            .filter_map(|id| self.application(id))
+            .chain(
+                self.applications.iter().filter(move |application| {
+                    application.mime_types.iter().any(|m| m == mime_type)
+                }),
+            )
+            .filter(move |application| {
+                removed.is_none_or(|removed| !removed.contains(&application.id))
+            })
+    }
+
+    pub fn default_application(&self, mime_type: &str) -> Option<&DesktopEntry> {
+        self.with_parents(mime_type).iter().find_map(|mime_type| {
+            self.defaults
+                .get(mime_type)
+                .into_iter()
+                .flatten()
+                .find_map(|id| self.application(id))
+                .or_else(|| self.associated(mime_type).next())
+        })
+    }
+
+    /// Applications that can open the type, the default first
+    pub fn candidates(&self, mime_type: &str) -> Vec<&DesktopEntry> {
+        let mut candidates = vec![];
+        candidates.extend(self.default_application(mime_type));
+        for mime_type in self.with_parents(mime_type) {
+            candidates.extend(
+                self.defaults
+                    .get(&mime_type)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| self.application(id)),
+            );
+            candidates.extend(self.associated(&mime_type));
+        }
+        candidates
+            .into_iter()
+            .unique_by(|application| &application.id)
+            .collect()
+    }
+
+    /// Writes the default to `$XDG_CONFIG_HOME/mimeapps.list`, keeping the rest of the file
+    pub fn set_default(&mut self, mime_type: &str, id: &str) -> io::Result<()> {
+        let Some(config_home) = &self.dirs.config_home else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "there is no config directory",
+            ));
+        };
+        let path = config_home.join(MIMEAPPS_LIST);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        std::fs::create_dir_all(config_home)?;
+        std::fs::write(&path, with_default(&text, mime_type, id))?;
+        *self = Self::load(mem::take(&mut self.dirs));
+        Ok(())
+    }
+}
+
+fn load_mime_apps(mut commands: Commands, mut notifications: EventWriter<Notification>) {
+    let mime_apps = MimeApps::load(SearchDirs::from_env());
+    if mime_apps.applications.is_empty() {
+        notifications.write(Notification::warning(
+            "No applications found, files can only be previewed",
+        ));
+    }
+    info!("found {} applications", mime_apps.applications.len());
+    commands.insert_resource(mime_apps);
+}
+
+pub fn mime_apps_plugin(app: &mut App) {
+    app.init_resource::<MimeApps>()
+        .add_command(RegisteredCommand::new("Reload applications", |commands| {
+            commands.run_system_cached(load_mime_apps);
+        }))
+        .add_systems(Startup, load_mime_apps);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    /// A directory tree standing in for the XDG base directories
+    fn fake_xdg(name: &str) -> (TestDir, SearchDirs) {
+        let root = TestDir::new(name);
+        let dirs = SearchDirs {
+            config_home: Some(root.join("config")),
+            config_dirs: vec![root.join("etc")],
+            data_home: Some(root.join("data")),
+            data_dirs: vec![root.join("usr")],
+        };
+        root.write(
+            "usr/mime/globs2",
+            "# weight:mime type:pattern\n\
+             50:text/plain:*.txt\n\
+             50:application/gzip:*.gz\n\
+             50:application/x-compressed-tar:*.tar.gz\n\
+             50:text/x-rust:*.rs\n\
+             50:text/x-c-header:*.H:cs\n\
+             50:text/markdown:*.md\n",
+        );
+        root.write(
+            "usr/mime/subclasses",
+            "application/x-compressed-tar application/gzip\n",
+        );
+        write_app(&root, "usr", "editor", "editor %F", "text/plain;");
+        write_app(
+            &root,
+            "usr",
+            "viewer",
+            "viewer --single %f",
+            "text/plain;text/markdown;",
+        );
+        write_app(&root, "usr", "archiver", "archiver %U", "application/gzip;");
+        (root, dirs)
+    }
+
+    /// `data_dir` is relative to `root`
+    fn write_app(root: &TestDir, data_dir: &str, id: &str, exec: &str, mime_types: &str) {
+        root.write(
+            format!("{data_dir}/applications/{id}.desktop"),
+            format!(
+                "[Desktop Entry]\nType=Application\nName={id}\nExec={exec}\nMimeType={mime_types}\n\n\
+                 [Desktop Action other]\nExec=ignored\n"
+            ),
+        );
+    }
+
+    fn ids(applications: Vec<&DesktopEntry>) -> Vec<&str> {
+        applications
+            .into_iter()
+            .map(|application| application.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn mime_type_from_globs() {
+        let (_root, dirs) = fake_xdg("globs");
+        let mime_apps = MimeApps::load(dirs);
+        let root = mime_apps.dirs.config_home.clone().unwrap();
+        let mime_type = |name: &str| mime_apps.mime_type(Path::new(name));
+        assert_eq!(mime_type("notes.txt"), "text/plain");
+        assert_eq!(mime_type("NOTES.TXT"), "text/plain");
+        assert_eq!(mime_type("backup.tar.gz"), "application/x-compressed-tar");
+        assert_eq!(mime_type("log.gz"), "application/gzip");
+        assert_eq!(mime_type("lib.H"), "text/x-c-header");
+        assert_ne!(mime_type("lib.h"), "text/x-c-header");
+        assert_eq!(
+            mime_apps.mime_type(root.parent().unwrap()),
+            DIRECTORY_MIME_TYPE
+        );
+    }
+
+    #[test]
+    fn mime_type_from_contents() {
+        let (root, dirs) = fake_xdg("contents");
+        let readme = root.write("data/README", "plain text");
+        let blob = root.write("data/blob", "\0\x01\x02");
+        let mime_apps = MimeApps::load(dirs);
+        assert_eq!(mime_apps.mime_type(&readme), TEXT_MIME_TYPE);
+        assert_eq!(mime_apps.mime_type(&blob), BINARY_MIME_TYPE);
+    }
+
+    #[test]
+    fn default_from_desktop_files() {
+        let (_root, dirs) = fake_xdg("desktop");
+        let mime_apps = MimeApps::load(dirs);
+        assert_eq!(
+            mime_apps
+                .default_application("text/plain")
+                .map(|a| a.id.as_str()),
+            Some("editor.desktop")
+        );
+        // through the text/plain parent
+        assert_eq!(
+            mime_apps
+                .default_application("text/x-rust")
+                .map(|a| a.id.as_str()),
+            Some("editor.desktop")
+        );
+        // through the subclasses file
+        assert_eq!(
+            mime_apps
+                .default_application("application/x-compressed-tar")
+                .map(|a| a.id.as_str()),
+            Some("archiver.desktop")
+        );
+        assert_eq!(mime_apps.default_application("image/png"), None);
+    }
+
+    #[test]
+    fn mimeapps_list_precedence() {
+        let (root, dirs) = fake_xdg("mimeapps");
+        root.write(
+            "config/mimeapps.list",
+            "[Default Applications]\ntext/plain=missing.desktop;viewer.desktop;\n",
+        );
+        root.write(
+            "etc/mimeapps.list",
+            "[Default Applications]\ntext/plain=editor.desktop;\n\
+             [Added Associations]\ntext/markdown=editor.desktop;\n\
+             [Removed Associations]\ntext/plain=editor.desktop;\n",
+        );
+        let mime_apps = MimeApps::load(dirs);
+        // the first installed default of the first file
+        assert_eq!(
+            mime_apps
+                .default_application("text/plain")
+                .map(|a| a.id.as_str()),
+            Some("viewer.desktop")
+        );
+        assert_eq!(
+            ids(mime_apps.candidates("text/markdown")),
+            ["editor.desktop", "viewer.desktop"]
+        );
+        // removed, but still listed as a default
+        assert_eq!(
+            ids(mime_apps.candidates("text/plain")),
+            ["viewer.desktop", "editor.desktop"]
+        );
+    }
+
+    #[test]
+    fn user_applications_override_system_ones() {
+        let (root, dirs) = fake_xdg("override");
+        write_app(&root, "data", "editor", "my-editor %f", "text/plain;");
+        root.write(
+            "data/applications/viewer.desktop",
+            "[Desktop Entry]\nType=Application\nExec=viewer\nHidden=true\n",
+        );
+        let mime_apps = MimeApps::load(dirs);
+        assert_eq!(
+            mime_apps
+                .application("editor.desktop")
+                .map(|a| a.exec.as_str()),
+            Some("my-editor %f")
+        );
+        assert_eq!(mime_apps.application("viewer.desktop"), None);
+    }
+
+    #[test]
+    fn set_default_keeps_other_entries() {
+        let (root, dirs) = fake_xdg("set-default");
+        let list = root.write(
+            "config/mimeapps.list",
+            "[Added Associations]\ntext/plain=viewer.desktop;\n\n\
+             [Default Applications]\nimage/png=gimp.desktop;\ntext/plain=editor.desktop;\n",
+        );
+        let mut mime_apps = MimeApps::load(dirs);
+        mime_apps
+            .set_default("text/plain", "viewer.desktop")
+            .unwrap();
+        assert_eq!(
+            mime_apps
+                .default_application("text/plain")
+                .map(|a| a.id.as_str()),
+            Some("viewer.desktop")
+        );
+        assert_eq!(
+            fs::read_to_string(&list).unwrap(),
+            "[Added Associations]\ntext/plain=viewer.desktop;\n\n\
+             [Default Applications]\nimage/png=gimp.desktop;\ntext/plain=viewer.desktop;\n"
+        );
+
+        mime_apps
+            .set_default("text/markdown", "editor.desktop")
+            .unwrap();
+        assert!(
+            fs::read_to_string(&list)
+                .unwrap()
+                .contains("[Default Applications]\ntext/markdown=editor.desktop;\n")
+        );
+    }
+
+    #[test]
+    fn set_default_creates_the_list() {
+        let (_root, dirs) = fake_xdg("create");
+        let mut mime_apps = MimeApps::load(dirs);
+        mime_apps
+            .set_default("text/plain", "viewer.desktop")
+            .unwrap();
+        let list = mime_apps
+            .dirs
+            .config_home
+            .clone()
+            .unwrap()
+            .join(MIMEAPPS_LIST);
+        assert_eq!(
+            fs::read_to_string(list).unwrap(),
+            "[Default Applications]\ntext/plain=viewer.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn exec_field_codes() {
+        let (_root, dirs) = fake_xdg("exec");
+        let mime_apps = MimeApps::load(dirs);
+        let paths = [PathBuf::from("/a b.txt"), PathBuf::from("/c.txt")];
+        let commands = |id: &str| mime_apps.application(id).unwrap().launch_commands(&paths);
+        assert_eq!(
+            commands("editor.desktop"),
+            [["editor", "/a b.txt", "/c.txt"]]
+        );
+        assert_eq!(
+            commands("viewer.desktop"),
+            [
+                ["viewer", "--single", "/a b.txt"],
+                ["viewer", "--single", "/c.txt"]
+            ]
+        );
+
+        let entry = DesktopEntry::parse(
+            "quoted.desktop".to_owned(),
+            PathBuf::from("/quoted.desktop"),
+            "[Desktop Entry]\nName=Quoted\nExec=\"/opt/my app/run\" --title=%c %i 100%% \"say \\\\\"hi\\\\\"\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            entry.launch_commands(&paths[..1]),
+            [[
+                "/opt/my app/run",
+                "--title=Quoted",
+                "100%",
+                "say \"hi\"",
+                "/a b.txt"
+            ]]
+        );
+    }
+}
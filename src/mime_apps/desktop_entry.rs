@@ -0,0 +1,175 @@
+use std::process::Command as Process;
+use std::{io, mem};
+
+use crate::mime_apps::ini::{parse_ini, split_list};
+use crate::mime_apps::spawn_detached;
+use crate::prelude::*;
+
+/// Escape sequences of desktop entry values
+fn unescape_value(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Splits the `Exec` value into arguments, which may be quoted with `"`
+fn split_exec(exec: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            '\\' if quoted => current.extend(chars.next()),
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// An application from a `.desktop` file
+#[derive(Clone, Debug, PartialEq)]
+pub struct DesktopEntry {
+    /// path below an `applications` directory with `/` replaced by `-`
+    pub id: String,
+    pub name: String,
+    pub(super) exec: String,
+    pub(super) mime_types: Vec<String>,
+    path: PathBuf,
+}
+
+impl DesktopEntry {
+    /// `None` for hidden entries and entries that are not applications
+    pub(super) fn parse(id: String, path: PathBuf, text: &str) -> Option<Self> {
+        let mut name = None;
+        let mut exec = None;
+        let mut mime_types = vec![];
+        for (group, key, value) in parse_ini(text) {
+            if group != "Desktop Entry" {
+                continue;
+            }
+            match key {
+                "Type" if value != "Application" => return None,
+                "Hidden" if value == "true" => return None,
+                "Name" => name = Some(unescape_value(value)),
+                "Exec" => exec = Some(unescape_value(value)),
+                "MimeType" => mime_types = split_list(value).map(str::to_owned).collect(),
+                _ => {}
+            }
+        }
+        Some(Self {
+            name: name.unwrap_or_else(|| id.clone()),
+            id,
+            exec: exec?,
+            mime_types,
+            path,
+        })
+    }
+
+    /// The processes to start, several if the application only takes one file at a time
+    pub fn launch_commands(&self, paths: &[PathBuf]) -> Vec<Vec<String>> {
+        let args = split_exec(&self.exec);
+        let takes_list = args.iter().any(|arg| arg == "%F" || arg == "%U");
+        let takes_one = args
+            .iter()
+            .any(|arg| arg.contains("%f") || arg.contains("%u"));
+        if takes_one && !takes_list && paths.len() > 1 {
+            paths
+                .iter()
+                .map(|path| self.expand_field_codes(&args, std::slice::from_ref(path)))
+                .collect()
+        } else {
+            vec![self.expand_field_codes(&args, paths)]
+        }
+    }
+
+    /// Paths are appended if the command has no field code for them
+    fn expand_field_codes(&self, args: &[String], paths: &[PathBuf]) -> Vec<String> {
+        let mut expanded = vec![];
+        let mut paths_used = false;
+        for arg in args {
+            match arg.as_str() {
+                "%F" | "%U" => {
+                    expanded.extend(paths.iter().map(|path| path.to_string_lossy().into_owned()));
+                    paths_used = true;
+                }
+                // there are no icons to pass
+                "%i" => {}
+                _ => {
+                    let mut value = String::new();
+                    let mut chars = arg.chars();
+                    while let Some(c) = chars.next() {
+                        if c != '%' {
+                            value.push(c);
+                            continue;
+                        }
+                        match chars.next() {
+                            Some('%') => value.push('%'),
+                            Some('f' | 'u') => {
+                                if let Some(path) = paths.first() {
+                                    value.push_str(&path.to_string_lossy());
+                                }
+                                paths_used = true;
+                            }
+                            Some('c') => value.push_str(&self.name),
+                            Some('k') => value.push_str(&self.path.to_string_lossy()),
+                            // deprecated codes are removed
+                            _ => {}
+                        }
+                    }
+                    if !value.is_empty() {
+                        expanded.push(value);
+                    }
+                }
+            }
+        }
+        if !paths_used {
+            expanded.extend(paths.iter().map(|path| path.to_string_lossy().into_owned()));
+        }
+        expanded
+    }
+
+    pub fn launch(&self, paths: &[PathBuf]) -> io::Result<()> {
+        for command in self.launch_commands(paths) {
+            let Some((program, args)) = command.split_first() else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} has no command", self.id),
+                ));
+            };
+            spawn_detached(Process::new(program).args(args))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+/// A pattern from shared-mime-info `globs2` files
+#[derive(Clone, Debug)]
+pub(super) struct MimeGlob {
+    pub weight: u32,
+    /// lowercase unless `case_sensitive`
+    pub pattern: String,
+    case_sensitive: bool,
+    pub mime_type: String,
+}
+
+impl MimeGlob {
+    /// Only the common `*.ext` and literal file name patterns are supported
+    pub fn matches(&self, file_name: &str) -> bool {
+        let file_name = if self.case_sensitive {
+            file_name.to_owned()
+        } else {
+            file_name.to_lowercase()
+        };
+        match self.pattern.strip_prefix('*') {
+            Some(suffix) if !suffix.contains(['*', '?', '[']) => file_name.ends_with(suffix),
+            Some(_) => false,
+            None => file_name == self.pattern,
+        }
+    }
+}
+
+pub(super) fn parse_globs2(text: &str) -> Vec<MimeGlob> {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let weight = fields.next()?.parse().ok()?;
+            let mime_type = fields.next()?.to_owned();
+            let pattern = fields.next()?;
+            let case_sensitive = fields.next().is_some_and(|flags| flags.contains("cs"));
+            Some(MimeGlob {
+                weight,
+                pattern: if case_sensitive {
+                    pattern.to_owned()
+                } else {
+                    pattern.to_lowercase()
+                },
+                case_sensitive,
+                mime_type,
+            })
+        })
+        .collect()
+}
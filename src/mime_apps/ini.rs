@@ -0,0 +1,24 @@
+/// `(group, key, value)` of every entry in an ini style file
+pub(super) fn parse_ini(text: &str) -> Vec<(&str, &str, &str)> {
+    let mut group = "";
+    let mut entries = vec![];
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            group = name;
+        } else if let Some((key, value)) = line.split_once('=') {
+            entries.push((group, key.trim(), value.trim()));
+        }
+    }
+    entries
+}
+
+/// Values of `;` separated lists
+pub(super) fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
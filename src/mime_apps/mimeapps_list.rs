@@ -0,0 +1,29 @@
+use crate::mime_apps::DEFAULT_APPLICATIONS;
+use crate::prelude::*;
+
+/// `text` of a mimeapps.list with the default for `mime_type` replaced
+pub(super) fn with_default(text: &str, mime_type: &str, id: &str) -> String {
+    let entry = format!("{mime_type}={id};");
+    let header = format!("[{DEFAULT_APPLICATIONS}]");
+    let mut lines = text.lines().map(str::to_owned).collect_vec();
+    let Some(start) = lines.iter().position(|line| line.trim() == header) else {
+        if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.extend([header, entry]);
+        return lines.join("\n") + "\n";
+    };
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |i| start + 1 + i);
+    let existing = lines[start + 1..end].iter().position(|line| {
+        line.split_once('=')
+            .is_some_and(|(key, _)| key.trim() == mime_type)
+    });
+    match existing {
+        Some(i) => lines[start + 1 + i] = entry,
+        None => lines.insert(start + 1, entry),
+    }
+    lines.join("\n") + "\n"
+}
@@ -0,0 +1,223 @@
+use crate::prelude::*;
+
+/// entries kept on either side of a [LocationHistory]
+const MAX_HISTORY_ENTRIES: usize = 100;
+
+/// How a directory change moves through the [LocationHistory]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Navigation {
+    /// somewhere new, which forgets the entries to go forward to
+    #[default]
+    Visit,
+    /// back when negative, forward when positive
+    Jump(isize),
+}
+
+/// The directories before and after the current one of a tab, like the history of a browser
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+pub struct LocationHistory {
+    /// the most recent last
+    back: Vec<PathBuf>,
+    /// the nearest last
+    next: Vec<PathBuf>,
+}
+
+impl LocationHistory {
+    /// Drops repeated entries, and the oldest ones when there are too many
+    pub fn new(mut back: Vec<PathBuf>, mut next: Vec<PathBuf>) -> Self {
+        back.dedup();
+        next.dedup();
+        let mut history = Self { back, next };
+        history.truncate();
+        history
+    }
+
+    /// The most recent last
+    pub fn back(&self) -> &[PathBuf] {
+        &self.back
+    }
+
+    /// The nearest last
+    pub fn next(&self) -> &[PathBuf] {
+        &self.next
+    }
+
+    /// Where jumping `steps` entries back or forward goes
+    pub fn target(&self, steps: isize) -> Option<&Path> {
+        let entries = match steps {
+            ..0 => &self.back,
+            1.. => &self.next,
+            0 => return None,
+        };
+        let index = entries.len().checked_sub(steps.unsigned_abs())?;
+        Some(&entries[index])
+    }
+
+    /// Records leaving `from`, after the directory changed
+    pub fn navigate(&mut self, navigation: Navigation, from: &Path) {
+        match navigation {
+            Navigation::Visit => {
+                self.next.clear();
+                push(&mut self.back, from);
+            }
+            Navigation::Jump(steps) => {
+                let (source, destination) = match steps {
+                    ..0 => (&mut self.back, &mut self.next),
+                    1.. => (&mut self.next, &mut self.back),
+                    0 => return,
+                };
+                let Some(index) = source.len().checked_sub(steps.unsigned_abs()) else {
+                    return;
+                };
+                // the target is where the directory changed to, the entries skipped over
+                // end up on the other side
+                let skipped = source.split_off(index);
+                push(destination, from);
+                for path in skipped.iter().skip(1).rev() {
+                    push(destination, path);
+                }
+            }
+        }
+        self.truncate();
+    }
+
+    fn truncate(&mut self) {
+        for entries in [&mut self.back, &mut self.next] {
+            let excess = entries.len().saturating_sub(MAX_HISTORY_ENTRIES);
+            entries.drain(..excess);
+        }
+    }
+}
+
+/// Skips `path` if it is already the last entry
+fn push(entries: &mut Vec<PathBuf>, path: &Path) {
+    if entries.last().is_none_or(|last| last != path) {
+        entries.push(path.to_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    /// Goes to `to` the way the fs plugin does, returns the new current directory
+    fn go(
+        history: &mut LocationHistory,
+        current: &str,
+        navigation: Navigation,
+        to: &str,
+    ) -> String {
+        history.navigate(navigation, Path::new(current));
+        to.to_owned()
+    }
+
+    fn visit_all(names: &[&str]) -> (LocationHistory, String) {
+        let mut history = LocationHistory::default();
+        let mut current = names[0].to_owned();
+        for name in &names[1..] {
+            current = go(&mut history, &current, Navigation::Visit, name);
+        }
+        (history, current)
+    }
+
+    fn jump(history: &mut LocationHistory, current: &str, steps: isize) -> String {
+        let target = history
+            .target(steps)
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        go(history, current, Navigation::Jump(steps), &target)
+    }
+
+    #[test]
+    fn visits_are_recorded_once() {
+        let (history, current) = visit_all(&["/a", "/b", "/c"]);
+        assert_eq!(current, "/c");
+        assert_eq!(history.back(), paths(&["/a", "/b"]));
+        assert!(history.next().is_empty());
+    }
+
+    #[test]
+    fn back_and_forward_round_trip() {
+        let (mut history, current) = visit_all(&["/a", "/b", "/c"]);
+        let current = jump(&mut history, &current, -1);
+        assert_eq!(current, "/b");
+        assert_eq!(history.back(), paths(&["/a"]));
+        assert_eq!(history.next(), paths(&["/c"]));
+
+        let current = jump(&mut history, &current, 1);
+        assert_eq!(current, "/c");
+        assert_eq!(history.back(), paths(&["/a", "/b"]));
+        assert!(history.next().is_empty());
+    }
+
+    #[test]
+    fn jumps_move_the_skipped_entries() {
+        let (mut history, current) = visit_all(&["/a", "/b", "/c", "/d"]);
+        let current = jump(&mut history, &current, -3);
+        assert_eq!(current, "/a");
+        assert!(history.back().is_empty());
+        assert_eq!(history.next(), paths(&["/d", "/c", "/b"]));
+
+        let current = jump(&mut history, &current, 2);
+        assert_eq!(current, "/c");
+        assert_eq!(history.back(), paths(&["/a", "/b"]));
+        assert_eq!(history.next(), paths(&["/d"]));
+    }
+
+    #[test]
+    fn a_new_visit_forgets_the_forward_entries() {
+        let (mut history, current) = visit_all(&["/a", "/b", "/c"]);
+        let current = jump(&mut history, &current, -2);
+        let current = go(&mut history, &current, Navigation::Visit, "/x");
+        assert_eq!(current, "/x");
+        assert_eq!(history.back(), paths(&["/a"]));
+        assert!(history.next().is_empty());
+        assert_eq!(history.target(1), None);
+    }
+
+    #[test]
+    fn targets_out_of_range_are_none() {
+        let (history, _) = visit_all(&["/a", "/b"]);
+        assert_eq!(history.target(-1), Some(Path::new("/a")));
+        assert_eq!(history.target(-2), None);
+        assert_eq!(history.target(0), None);
+        assert_eq!(history.target(1), None);
+    }
+
+    #[test]
+    fn jumps_out_of_range_change_nothing() {
+        let (mut history, _) = visit_all(&["/a", "/b"]);
+        let before = history.clone();
+        history.navigate(Navigation::Jump(-5), Path::new("/b"));
+        history.navigate(Navigation::Jump(1), Path::new("/b"));
+        assert_eq!(history, before);
+    }
+
+    #[test]
+    fn repeated_entries_are_dropped() {
+        let (mut history, current) = visit_all(&["/a", "/b"]);
+        // e.g. after the directory of an entry was removed and its parent is shown instead
+        history.navigate(Navigation::Visit, Path::new("/a"));
+        assert_eq!(history.back(), paths(&["/a"]));
+        assert_eq!(current, "/b");
+
+        let history = LocationHistory::new(paths(&["/a", "/a", "/b"]), paths(&["/c", "/c"]));
+        assert_eq!(history.back(), paths(&["/a", "/b"]));
+        assert_eq!(history.next(), paths(&["/c"]));
+    }
+
+    #[test]
+    fn the_oldest_entries_are_dropped() {
+        let names = (0..=MAX_HISTORY_ENTRIES + 10)
+            .map(|i| format!("/{i}"))
+            .collect_vec();
+        let (history, _) = visit_all(&names.iter().map(String::as_str).collect_vec());
+        assert_eq!(history.back().len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history.back()[0], PathBuf::from("/10"));
+    }
+}
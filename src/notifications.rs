@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+use derive_more::Display;
+
+use crate::prelude::*;
+
+/// number of notifications kept around for the log panel
+const NOTIFICATION_LOG_CAPACITY: usize = 200;
+
+#[derive(Clone, Copy, Debug, Display, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Info => Color::srgb(0.15, 0.39, 0.92),
+            Self::Warning => Color::srgb(0.85, 0.47, 0.02),
+            Self::Error => Color::srgb(0.86, 0.15, 0.15),
+        }
+    }
+}
+
+/// A user-facing message, shown as a toast and kept in the [NotificationLog]
+#[derive(Clone, Debug, Event)]
+pub struct Notification {
+    pub severity: Severity,
+    pub message: String,
+    pub timestamp: DateTime<Local>,
+}
+
+impl Notification {
+    pub fn new<S: Into<String>>(severity: Severity, message: S) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            timestamp: Local::now(),
+        }
+    }
+    pub fn info<S: Into<String>>(message: S) -> Self {
+        Self::new(Severity::Info, message)
+    }
+    pub fn warning<S: Into<String>>(message: S) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+    pub fn error<S: Into<String>>(message: S) -> Self {
+        Self::new(Severity::Error, message)
+    }
+    pub fn time_label(&self) -> String {
+        self.timestamp.format("%H:%M:%S").to_string()
+    }
+}
+
+/// Most recent notifications, oldest first
+#[derive(Debug, Default, Deref, Resource)]
+pub struct NotificationLog(VecDeque<Notification>);
+
+impl NotificationLog {
+    fn push(&mut self, notification: Notification) {
+        if self.0.len() == NOTIFICATION_LOG_CAPACITY {
+            self.0.pop_front();
+        }
+        self.0.push_back(notification);
+    }
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+fn collect_notifications(
+    mut notifications: EventReader<Notification>,
+    mut log: ResMut<NotificationLog>,
+) {
+    for notification in notifications.read() {
+        log.push(notification.clone());
+    }
+}
+
+pub fn notifications_plugin(app: &mut App) {
+    app.add_event::<Notification>()
+        .init_resource::<NotificationLog>()
+        .add_systems(
+            FixedUpdate,
+            collect_notifications.run_if(on_event::<Notification>),
+        );
+}
@@ -0,0 +1,228 @@
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::ffi::OsStringExt;
+
+use crate::prelude::*;
+use crate::xdg;
+
+/// in `$XDG_CONFIG_HOME`, written by `xdg-user-dirs-update`
+const USER_DIRS_FILE: &str = "user-dirs.dirs";
+
+/// one mount per line: `device mount_point type options dump pass`
+const MOUNTS_FILE: &str = "/proc/self/mounts";
+
+/// mount points of the system itself and below them, not volumes to browse, besides `/`
+const SYSTEM_MOUNTS: &[&str] = &["/boot", "/efi", "/nix", "/snap", "/usr", "/var"];
+
+/// Well known directories of the user, see `man user-dirs.dirs`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UserDir {
+    Desktop,
+    Documents,
+    Downloads,
+    Pictures,
+}
+
+impl UserDir {
+    pub const ALL: [Self; 4] = [
+        Self::Desktop,
+        Self::Documents,
+        Self::Downloads,
+        Self::Pictures,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Desktop => "Desktop",
+            Self::Documents => "Documents",
+            Self::Downloads => "Downloads",
+            Self::Pictures => "Pictures",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Self::Desktop => "XDG_DESKTOP_DIR",
+            Self::Documents => "XDG_DOCUMENTS_DIR",
+            Self::Downloads => "XDG_DOWNLOAD_DIR",
+            Self::Pictures => "XDG_PICTURES_DIR",
+        }
+    }
+
+    /// As configured in `user-dirs.dirs`, or `$HOME/<name>`
+    pub fn path(self) -> Option<PathBuf> {
+        let home = xdg::home_dir()?;
+        let configured = xdg::config_home()
+            .and_then(|dir| fs::read_to_string(dir.join(USER_DIRS_FILE)).ok())
+            .and_then(|text| parse_user_dir(&text, self.key(), &home));
+        Some(configured.unwrap_or_else(|| home.join(self.name())))
+    }
+}
+
+/// Finds a line like `XDG_DESKTOP_DIR="$HOME/Desktop"`, values are absolute or start with `$HOME`
+fn parse_user_dir(text: &str, key: &str, home: &Path) -> Option<PathBuf> {
+    let value = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .find_map(|line| line.strip_prefix(key)?.trim_start().strip_prefix('='))?
+        .trim()
+        .trim_matches('"');
+    match value.strip_prefix("$HOME") {
+        Some(relative) => Some(home.join(relative.trim_start_matches('/'))),
+        None if value.starts_with('/') => Some(PathBuf::from(value)),
+        None => None,
+    }
+}
+
+/// Mount points of devices like USB drives and other partitions, sorted
+pub fn mounted_volumes() -> Vec<PathBuf> {
+    let text = match fs::read_to_string(MOUNTS_FILE) {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("could not read {MOUNTS_FILE}: {e}");
+            return vec![];
+        }
+    };
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?;
+            let mount_point = unescape_mount_point(fields.next()?);
+            let is_system = mount_point == Path::new("/")
+                || SYSTEM_MOUNTS
+                    .iter()
+                    .any(|system| mount_point.starts_with(system));
+            (device.starts_with("/dev/") && !is_system).then_some(mount_point)
+        })
+        .sorted()
+        .dedup()
+        .collect()
+}
+
+/// Spaces and other whitespace are written as octal escapes like `\040`,
+/// escaped bytes need not be UTF-8 on their own
+fn unescape_mount_point(field: &str) -> PathBuf {
+    let bytes = field.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'\\')
+            .then(|| field.get(i + 1..i + 4))
+            .flatten()
+            .and_then(|octal| u8::from_str_radix(octal, 8).ok());
+        match escaped {
+            Some(byte) => {
+                unescaped.push(byte);
+                i += 4;
+            }
+            None => {
+                unescaped.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(OsString::from_vec(unescaped))
+}
+
+/// `~` and `~/path` are below `$HOME`, other paths are returned as they are
+pub fn expand_home(path: &Path) -> PathBuf {
+    let Ok(relative) = path.strip_prefix("~") else {
+        return path.to_owned();
+    };
+    match xdg::home_dir() {
+        Some(home) => home.join(relative),
+        None => path.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::ffi::OsStrExt;
+
+    use super::*;
+
+    const USER_DIRS: &str = r#"
+# This file is written by xdg-user-dirs-update
+XDG_DESKTOP_DIR="$HOME/Desktop"
+XDG_DOWNLOAD_DIR = "$HOME/Téléchargements"
+XDG_DOCUMENTS_DIR="/srv/documents"
+XDG_PICTURES_DIR="$HOME"
+#XDG_MUSIC_DIR="$HOME/Music"
+XDG_VIDEOS_DIR="Videos"
+"#;
+
+    fn user_dir(key: &str) -> Option<PathBuf> {
+        parse_user_dir(USER_DIRS, key, Path::new("/home/user"))
+    }
+
+    #[test]
+    fn user_dirs_below_home() {
+        assert_eq!(
+            user_dir("XDG_DESKTOP_DIR"),
+            Some(PathBuf::from("/home/user/Desktop"))
+        );
+        assert_eq!(
+            user_dir("XDG_DOWNLOAD_DIR"),
+            Some(PathBuf::from("/home/user/Téléchargements"))
+        );
+        assert_eq!(
+            user_dir("XDG_PICTURES_DIR"),
+            Some(PathBuf::from("/home/user"))
+        );
+    }
+
+    #[test]
+    fn user_dirs_absolute_relative_and_missing() {
+        assert_eq!(
+            user_dir("XDG_DOCUMENTS_DIR"),
+            Some(PathBuf::from("/srv/documents"))
+        );
+        assert_eq!(user_dir("XDG_VIDEOS_DIR"), None);
+        assert_eq!(user_dir("XDG_MUSIC_DIR"), None);
+        assert_eq!(user_dir("XDG_TEMPLATES_DIR"), None);
+    }
+
+    #[test]
+    fn mount_point_escapes() {
+        assert_eq!(
+            unescape_mount_point(r"/media/user/My\040Drive"),
+            PathBuf::from("/media/user/My Drive")
+        );
+        assert_eq!(
+            unescape_mount_point(r"/mnt/tab\011and\134backslash"),
+            PathBuf::from("/mnt/tab\tand\\backslash")
+        );
+        // not an escape, kept as it is
+        assert_eq!(
+            unescape_mount_point(r"/mnt/a\9b\"),
+            PathBuf::from(r"/mnt/a\9b\")
+        );
+    }
+
+    #[test]
+    fn mount_point_non_ascii() {
+        assert_eq!(
+            unescape_mount_point("/media/ünïcödé\\040日本"),
+            PathBuf::from("/media/ünïcödé 日本")
+        );
+        // escaped bytes of a name that is not UTF-8
+        assert_eq!(
+            unescape_mount_point(r"/mnt/\377\376")
+                .as_os_str()
+                .as_bytes(),
+            b"/mnt/\xff\xfe"
+        );
+    }
+
+    #[test]
+    fn expand_home_only_expands_a_leading_tilde() {
+        assert_eq!(expand_home(Path::new("/tmp/~")), PathBuf::from("/tmp/~"));
+        assert_eq!(expand_home(Path::new("~user/a")), PathBuf::from("~user/a"));
+        assert_eq!(expand_home(Path::new("a/~")), PathBuf::from("a/~"));
+        if let Some(home) = xdg::home_dir() {
+            assert_eq!(expand_home(Path::new("~")), home);
+            assert_eq!(expand_home(Path::new("~/a/b")), home.join("a/b"));
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::{fs, io};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::bridge::CurrentDirectoryChanged;
+use crate::prelude::*;
+use crate::resources::{ActiveTab, CurrentDirectory};
+use crate::xdg;
+
+/// in `$XDG_STATE_HOME/corvus`
+const RECENT_LOCATIONS_FILE: &str = "recent_locations.ron";
+
+/// older visits are forgotten
+const MAX_VISITS: usize = 1000;
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+
+/// How much a visit counts up to an age in seconds, recent visits count for more
+const VISIT_WEIGHTS: &[(i64, u32)] = &[
+    (HOUR, 120),
+    (DAY, 100),
+    (4 * DAY, 80),
+    (14 * DAY, 60),
+    (31 * DAY, 40),
+    (90 * DAY, 20),
+];
+
+/// for visits older than any of [VISIT_WEIGHTS]
+const OLD_VISIT_WEIGHT: u32 = 10;
+
+fn visit_weight(age: i64) -> u32 {
+    VISIT_WEIGHTS
+        .iter()
+        .find(|(max_age, _)| age <= *max_age)
+        .map_or(OLD_VISIT_WEIGHT, |(_, weight)| *weight)
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Visit {
+    pub path: PathBuf,
+    /// seconds since the epoch
+    pub time: i64,
+}
+
+impl Visit {
+    pub fn local_time(&self) -> Option<DateTime<Local>> {
+        DateTime::from_timestamp(self.time, 0).map(|time| time.with_timezone(&Local))
+    }
+}
+
+/// Every directory that was visited in any tab, oldest first
+#[derive(Debug, Default, Deserialize, Resource, Serialize)]
+#[serde(default)]
+pub struct RecentLocations {
+    visits: Vec<Visit>,
+}
+
+impl RecentLocations {
+    /// Newest first
+    pub fn visits(&self) -> impl Iterator<Item = &Visit> {
+        self.visits.iter().rev()
+    }
+
+    pub fn record(&mut self, path: PathBuf, time: i64) {
+        // reloading the same directory is not a new visit
+        if self.visits.last().is_some_and(|last| last.path == path) {
+            return;
+        }
+        self.visits.push(Visit { path, time });
+        let excess = self.visits.len().saturating_sub(MAX_VISITS);
+        self.visits.drain(..excess);
+    }
+
+    /// Each visited directory once, the most frequent and recent first
+    pub fn ranked(&self, now: i64) -> Vec<&Path> {
+        let mut scores: HashMap<&Path, (u32, i64)> = HashMap::new();
+        for visit in &self.visits {
+            let (score, last) = scores.entry(&visit.path).or_default();
+            *score += visit_weight(now - visit.time);
+            *last = visit.time;
+        }
+        let mut scores = scores.into_iter().collect_vec();
+        scores.sort_by(|(a_path, (a, a_last)), (b_path, (b, b_last))| {
+            b.cmp(a).then(b_last.cmp(a_last)).then(a_path.cmp(b_path))
+        });
+        scores.into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+fn recent_locations_path() -> Option<PathBuf> {
+    xdg::state_dir().map(|dir| dir.join(RECENT_LOCATIONS_FILE))
+}
+
+fn load_recent_locations(mut recent: ResMut<RecentLocations>) {
+    let Some(path) = recent_locations_path() else {
+        return;
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => match ron::from_str(&text) {
+            Ok(loaded) => *recent = loaded,
+            Err(e) => warn!("invalid {path:?}: {e}"),
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => warn!("could not read {path:?}: {e}"),
+    }
+}
+
+fn save_recent_locations(recent: Res<RecentLocations>) {
+    let Some(path) = recent_locations_path() else {
+        return;
+    };
+    let result = ron::to_string(&*recent)
+        .map_err(io::Error::other)
+        .and_then(|text| {
+            path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, text))
+        });
+    if let Err(e) = result {
+        warn!("could not save the recent locations to {path:?}: {e}");
+    }
+}
+
+fn record_visit(mut recent: ResMut<RecentLocations>, directory: ActiveTab<&CurrentDirectory>) {
+    if let Some(directory) = directory.get() {
+        recent.record(directory.to_path_buf(), Local::now().timestamp());
+    }
+}
+
+pub fn recent_locations_plugin(app: &mut App) {
+    app.init_resource::<RecentLocations>()
+        .add_systems(Startup, load_recent_locations)
+        .add_systems(
+            Update,
+            (
+                record_visit.run_if(on_event::<CurrentDirectoryChanged>),
+                save_recent_locations.run_if(
+                    resource_changed::<RecentLocations>.and(not(resource_added::<RecentLocations>)),
+                ),
+            )
+                .chain(),
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recent(visits: &[(&str, i64)]) -> RecentLocations {
+        let mut recent = RecentLocations::default();
+        for (path, time) in visits {
+            recent.record(PathBuf::from(path), *time);
+        }
+        recent
+    }
+
+    fn paths<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Vec<&'a str> {
+        paths
+            .into_iter()
+            .map(|path| path.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn record_skips_reloads() {
+        let recent = recent(&[("/a", 1), ("/a", 2), ("/b", 3), ("/a", 4)]);
+        assert_eq!(
+            paths(recent.visits().map(|visit| visit.path.as_path())),
+            ["/a", "/b", "/a"]
+        );
+        assert_eq!(recent.visits().next().unwrap().time, 4);
+    }
+
+    #[test]
+    fn record_forgets_the_oldest_visits() {
+        let mut recent = RecentLocations::default();
+        for time in 0..MAX_VISITS as i64 + 10 {
+            recent.record(PathBuf::from(format!("/{}", time % 2)), time);
+        }
+        assert_eq!(recent.visits().count(), MAX_VISITS);
+        assert_eq!(recent.visits().last().unwrap().time, 10);
+    }
+
+    #[test]
+    fn ranked_by_frequency() {
+        let now = 10 * DAY;
+        let recent = recent(&[("/a", now), ("/b", now), ("/c", now), ("/b", now)]);
+        assert_eq!(paths(recent.ranked(now)), ["/b", "/a", "/c"]);
+    }
+
+    #[test]
+    fn recent_visits_count_for_more() {
+        let now = 100 * DAY;
+        // two old visits are worth less than one of the last hour
+        let recent = recent(&[("/old", 0), ("/other", 1), ("/old", 2), ("/new", now)]);
+        assert_eq!(paths(recent.ranked(now)), ["/new", "/old", "/other"]);
+    }
+
+    #[test]
+    fn equal_scores_by_last_visit() {
+        let now = 10 * DAY;
+        let recent = recent(&[("/a", now - 3), ("/b", now - 2), ("/c", now - 1)]);
+        assert_eq!(paths(recent.ranked(now)), ["/c", "/b", "/a"]);
+    }
+}
@@ -1,12 +1,18 @@
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::Display;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use bevy::ecs::query::{QueryData, ROQueryItem};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use derive_more::{Display, From};
+use serde::{Deserialize, Serialize};
 
 use crate::fs::ResolvedEntry;
+use crate::navigation::LocationHistory;
 
-#[derive(Clone, Copy, Debug, Default, Display, PartialEq, Resource)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Display, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PanelLayout {
     #[default]
     Automatic,
@@ -14,19 +20,351 @@ pub enum PanelLayout {
     Vertical,
 }
 
-#[derive(Debug, Deref, DerefMut, Resource)]
+impl PanelLayout {
+    /// Horizontal or Vertical, Automatic follows the shape of the window
+    pub fn resolve(self, window_size: Vec2) -> Self {
+        match self {
+            Self::Automatic if window_size.x >= window_size.y => Self::Horizontal,
+            Self::Automatic => Self::Vertical,
+            layout => layout,
+        }
+    }
+    /// Panels side by side, or stacked
+    pub fn flex_direction(self, window_size: Vec2) -> FlexDirection {
+        match self.resolve(window_size) {
+            Self::Vertical => FlexDirection::Column,
+            _ => FlexDirection::Row,
+        }
+    }
+}
+
+/// How the navigation header shows the current directory
+#[derive(Clone, Copy, Debug, Default, Deserialize, Display, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LocationBar {
+    /// a button per path component
+    #[default]
+    Breadcrumbs,
+    Text,
+}
+
+impl LocationBar {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Breadcrumbs => Self::Text,
+            Self::Text => Self::Breadcrumbs,
+        }
+    }
+}
+
+/// How the entries of the current directory are shown
+#[derive(Clone, Copy, Debug, Default, Deserialize, Display, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverviewMode {
+    #[default]
+    Grid,
+    /// the ancestors are listed in columns before the current directory, the preview after it
+    Columns,
+}
+
+impl OverviewMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Grid => Self::Columns,
+            Self::Columns => Self::Grid,
+        }
+    }
+}
+
+/// One explorer tab, the state of the tab is in the other components of its entity
+#[derive(Component, Debug, Default)]
+#[require(
+    LocationHistory,
+    DirectoryEntries,
+    EntrySelection,
+    PreviewPath,
+    PreviewLine
+)]
+pub struct ExplorerTab;
+
+/// The explorer tabs in the order they are shown
+#[derive(Debug, Default, Resource)]
+pub struct ExplorerTabs {
+    tabs: Vec<Entity>,
+    /// index into `tabs`
+    active: usize,
+    /// most recently closed last
+    closed: Vec<ClosedTab>,
+    /// the second pane of the dual pane mode, not one of `tabs`
+    other_pane: Option<Entity>,
+    /// shown before the active tab
+    other_pane_first: bool,
+}
+
+/// What reopening a tab brings back
+#[derive(Debug)]
+pub struct ClosedTab {
+    pub directory: PathBuf,
+    pub history: LocationHistory,
+    /// where the tab was
+    pub index: usize,
+}
+
+impl ExplorerTabs {
+    pub fn tabs(&self) -> &[Entity] {
+        &self.tabs
+    }
+    pub fn active(&self) -> Option<Entity> {
+        self.tabs.get(self.active).copied()
+    }
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+    /// Adds `tab` at `index`, or at the end, and makes it active
+    pub fn insert(&mut self, index: Option<usize>, tab: Entity) {
+        let index = index.unwrap_or(self.tabs.len()).min(self.tabs.len());
+        self.tabs.insert(index, tab);
+        self.active = index;
+    }
+    /// Returns false if `tab` is not one of the tabs
+    pub fn activate(&mut self, tab: Entity) -> bool {
+        let Some(index) = self.tabs.iter().position(|e| *e == tab) else {
+            return false;
+        };
+        self.active = index;
+        true
+    }
+    /// Activates the tab `step` tabs away from the active one, wrapping around
+    pub fn cycle(&mut self, step: isize) {
+        if !self.tabs.is_empty() {
+            self.active =
+                (self.active as isize + step).rem_euclid(self.tabs.len() as isize) as usize;
+        }
+    }
+    /// Moves the active tab `step` places, it stays active
+    pub fn move_active(&mut self, step: isize) {
+        let Some(last) = self.tabs.len().checked_sub(1) else {
+            return;
+        };
+        let index = self.active.saturating_add_signed(step).min(last);
+        let tab = self.tabs.remove(self.active);
+        self.tabs.insert(index, tab);
+        self.active = index;
+    }
+    /// Removes the tab and remembers it for [Self::reopen], the next tab becomes active
+    pub fn close(&mut self, tab: Entity, closed: ClosedTab) {
+        let Some(index) = self.tabs.iter().position(|e| *e == tab) else {
+            return;
+        };
+        self.tabs.remove(index);
+        if index < self.active || self.active >= self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+        self.closed.push(closed);
+    }
+    /// The most recently closed tab
+    pub fn reopen(&mut self) -> Option<ClosedTab> {
+        self.closed.pop()
+    }
+    pub fn other_pane(&self) -> Option<Entity> {
+        self.other_pane
+    }
+    pub fn other_pane_first(&self) -> bool {
+        self.other_pane_first
+    }
+    /// Turns the dual pane mode on with `pane`, or off with `None`
+    pub fn set_other_pane(&mut self, pane: Option<Entity>) {
+        self.other_pane = pane;
+        self.other_pane_first = false;
+    }
+    /// Exchanges the active tab and the other pane, so their directories trade places
+    pub fn swap_panes(&mut self) {
+        if let Some(other) = self.other_pane.as_mut()
+            && let Some(active) = self.tabs.get_mut(self.active)
+        {
+            std::mem::swap(active, other);
+        }
+    }
+    /// Makes the other pane the active one, the panes stay where they are
+    pub fn switch_pane(&mut self) {
+        if self.other_pane.is_some() {
+            self.swap_panes();
+            self.other_pane_first = !self.other_pane_first;
+        }
+    }
+}
+
+/// The components `D` of the active [ExplorerTab]
+#[derive(SystemParam)]
+pub struct ActiveTab<'w, 's, D: QueryData + 'static> {
+    tabs: Res<'w, ExplorerTabs>,
+    query: Query<'w, 's, D, With<ExplorerTab>>,
+}
+
+impl<D: QueryData + 'static> ActiveTab<'_, '_, D> {
+    pub fn get(&self) -> Option<ROQueryItem<'_, D>> {
+        self.query.get(self.tabs.active()?).ok()
+    }
+    pub fn get_mut(&mut self) -> Option<D::Item<'_>> {
+        self.query.get_mut(self.tabs.active()?).ok()
+    }
+}
+
+#[derive(Component, Debug, Deref, DerefMut)]
 pub struct CurrentDirectory(PathBuf);
 
-#[derive(Debug, Default, Deref, DerefMut, Resource)]
+#[derive(Component, Debug, Default, Deref, DerefMut)]
 pub struct DirectoryEntries(Vec<ResolvedEntry>);
 
-#[derive(Debug, Default, Deref, DerefMut, From, Resource)]
+/// Directories listed besides the ones of the tabs, like the expanded ones of the directory tree.
+/// Only what is shown is kept, and listed again after entries were changed
+#[derive(Debug, Default, Resource)]
+pub struct DirectoryListings(HashMap<PathBuf, Option<Vec<ResolvedEntry>>>);
+
+impl DirectoryListings {
+    /// `None` while it is loading, or when it was never requested
+    pub fn get(&self, path: &Path) -> Option<&[ResolvedEntry]> {
+        self.0.get(path)?.as_deref()
+    }
+    pub fn is_requested(&self, path: &Path) -> bool {
+        self.0.contains_key(path)
+    }
+    /// Entries that are already listed stay until the new ones are loaded
+    pub fn request(&mut self, path: PathBuf) {
+        self.0.entry(path).or_default();
+    }
+    /// Ignored for directories that were not requested
+    pub fn resolve(&mut self, path: &Path, entries: Vec<ResolvedEntry>) {
+        if let Some(listing) = self.0.get_mut(path) {
+            *listing = Some(entries);
+        }
+    }
+    /// Once it is no longer shown, showing it again needs a new request
+    pub fn forget(&mut self, path: &Path) {
+        self.0.remove(path);
+    }
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.0.keys().map(PathBuf::as_path)
+    }
+}
+
+#[derive(Component, Debug, Default, Deref, DerefMut, From)]
 pub struct PreviewPath(Option<PathBuf>);
 
-#[derive(Debug, Default, From, Resource)]
-pub struct LocationHistory {
-    pub back: Vec<PathBuf>,
-    pub next: Vec<PathBuf>,
+/// line of the text preview to highlight and scroll to, 1-based
+#[derive(Component, Debug, Default, Deref, DerefMut, From)]
+pub struct PreviewLine(Option<usize>);
+
+/// Rows of the overview, the focused one and the selected ones
+#[derive(Component, Debug, Default)]
+pub struct EntrySelection {
+    /// paths in the order they are listed
+    rows: Vec<PathBuf>,
+    focused: Option<usize>,
+    /// where Shift selections start
+    anchor: Option<usize>,
+    selected: BTreeSet<usize>,
+}
+
+impl EntrySelection {
+    /// Replaces the listed rows, paths that are still listed keep their state
+    pub fn set_rows(&mut self, rows: Vec<PathBuf>) {
+        let indices: HashMap<&PathBuf, usize> =
+            rows.iter().enumerate().map(|(i, row)| (row, i)).collect();
+        let moved = |index: &usize| {
+            self.rows
+                .get(*index)
+                .and_then(|path| indices.get(path))
+                .copied()
+        };
+        let focused = self.focused.as_ref().and_then(moved);
+        let anchor = self.anchor.as_ref().and_then(moved);
+        let selected = self.selected.iter().filter_map(moved).collect();
+        *self = Self {
+            rows,
+            focused,
+            anchor,
+            selected,
+        };
+    }
+    pub fn rows(&self) -> &[PathBuf] {
+        &self.rows
+    }
+    pub fn focused(&self) -> Option<usize> {
+        self.focused
+    }
+    pub fn focused_path(&self) -> Option<&Path> {
+        self.focused.map(|i| self.rows[i].as_path())
+    }
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.contains(&index)
+    }
+    pub fn selected_count(&self) -> usize {
+        self.selected.len()
+    }
+    /// in the order they are listed
+    pub fn selected_paths(&self) -> impl Iterator<Item = &Path> {
+        self.selected.iter().map(|i| self.rows[*i].as_path())
+    }
+    /// What commands act on: the selected paths, or the focused one if nothing is selected
+    pub fn targets(&self) -> Vec<&Path> {
+        match self.selected.is_empty() {
+            true => self.focused_path().into_iter().collect(),
+            false => self.selected_paths().collect(),
+        }
+    }
+    /// Like clicking a row: only selects it, or `toggle`s it (Ctrl),
+    /// or selects the `range` from the last clicked row (Shift)
+    pub fn click(&mut self, index: usize, toggle: bool, range: bool) {
+        if index >= self.rows.len() {
+            return;
+        }
+        match (self.anchor, range) {
+            (Some(anchor), true) => {
+                if !toggle {
+                    self.selected.clear();
+                }
+                self.selected.extend(anchor.min(index)..=anchor.max(index));
+            }
+            _ if toggle => {
+                if !self.selected.remove(&index) {
+                    self.selected.insert(index);
+                }
+                self.anchor = Some(index);
+            }
+            _ => {
+                self.selected = BTreeSet::from([index]);
+                self.anchor = Some(index);
+            }
+        }
+        self.focused = Some(index);
+    }
+    /// Moves the focus `step` rows, selecting the focused row, or the range up to it when `extend`ing.
+    /// Without a focused row, starts from the first or last row
+    pub fn move_focus(&mut self, step: isize, extend: bool) {
+        let Some(last) = self.rows.len().checked_sub(1) else {
+            return;
+        };
+        let index = match self.focused {
+            Some(index) => index.saturating_add_signed(step).min(last),
+            None if step < 0 => last,
+            None => 0,
+        };
+        self.click(index, false, extend);
+    }
+    pub fn select_all(&mut self) {
+        self.selected = (0..self.rows.len()).collect();
+    }
+    pub fn invert(&mut self) {
+        self.selected = (0..self.rows.len())
+            .filter(|i| !self.selected.contains(i))
+            .collect();
+    }
+    /// Keeps the focus
+    pub fn clear(&mut self) {
+        self.selected.clear();
+        self.anchor = None;
+    }
 }
 
 impl From<PathBuf> for CurrentDirectory {
@@ -44,3 +382,233 @@ impl Display for CurrentDirectory {
         write!(f, "{text}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    fn selection(rows: &[&str]) -> EntrySelection {
+        let mut selection = EntrySelection::default();
+        selection.set_rows(rows.iter().map(PathBuf::from).collect());
+        selection
+    }
+
+    fn selected(selection: &EntrySelection) -> Vec<usize> {
+        selection.selected.iter().copied().collect()
+    }
+
+    fn tabs(count: u32) -> (ExplorerTabs, Vec<Entity>) {
+        let mut tabs = ExplorerTabs::default();
+        let entities = (1..=count).map(Entity::from_raw).collect_vec();
+        for tab in &entities {
+            tabs.insert(None, *tab);
+        }
+        (tabs, entities)
+    }
+
+    fn closed(index: usize) -> ClosedTab {
+        ClosedTab {
+            directory: PathBuf::from(format!("/{index}")),
+            history: default(),
+            index,
+        }
+    }
+
+    #[test]
+    fn insert_activates_the_new_tab() {
+        let (mut tabs, e) = tabs(2);
+        assert_eq!(tabs.active(), Some(e[1]));
+        let new = Entity::from_raw(10);
+        tabs.insert(Some(1), new);
+        assert_eq!(tabs.tabs(), [e[0], new, e[1]]);
+        assert_eq!(tabs.active(), Some(new));
+        // past the end appends
+        let last = Entity::from_raw(11);
+        tabs.insert(Some(10), last);
+        assert_eq!(tabs.tabs(), [e[0], new, e[1], last]);
+        assert_eq!(tabs.active_index(), 3);
+    }
+
+    #[test]
+    fn close_keeps_a_neighbour_active_and_reopen_restores_the_index() {
+        let (mut tabs, e) = tabs(4);
+        tabs.activate(e[1]);
+        // the next tab takes over
+        tabs.close(e[1], closed(1));
+        assert_eq!(tabs.active(), Some(e[2]));
+        // closing a tab before the active one keeps the active one
+        tabs.close(e[0], closed(0));
+        assert_eq!(tabs.active(), Some(e[2]));
+        // closing the last tab activates the one before it
+        tabs.activate(e[3]);
+        tabs.close(e[3], closed(1));
+        assert_eq!(tabs.active(), Some(e[2]));
+        // unknown tabs are ignored
+        tabs.close(Entity::from_raw(10), closed(5));
+        assert_eq!(tabs.tabs(), [e[2]]);
+
+        let reopened = tabs.reopen().unwrap();
+        assert_eq!(reopened.index, 1);
+        assert_eq!(reopened.directory, Path::new("/1"));
+        assert_eq!(tabs.reopen().unwrap().index, 0);
+        assert_eq!(tabs.reopen().unwrap().index, 1);
+        assert!(tabs.reopen().is_none());
+    }
+
+    #[test]
+    fn close_the_only_tab() {
+        let (mut tabs, e) = tabs(1);
+        tabs.close(e[0], closed(0));
+        assert!(tabs.tabs().is_empty());
+        assert_eq!(tabs.active(), None);
+        tabs.cycle(1);
+        tabs.move_active(1);
+        assert_eq!(tabs.active(), None);
+    }
+
+    #[test]
+    fn cycle_wraps_around() {
+        let (mut tabs, e) = tabs(3);
+        tabs.cycle(1);
+        assert_eq!(tabs.active(), Some(e[0]));
+        tabs.cycle(-1);
+        assert_eq!(tabs.active(), Some(e[2]));
+        tabs.cycle(-4);
+        assert_eq!(tabs.active(), Some(e[1]));
+    }
+
+    #[test]
+    fn move_active_stays_in_bounds() {
+        let (mut tabs, e) = tabs(3);
+        tabs.activate(e[0]);
+        tabs.move_active(1);
+        assert_eq!(tabs.tabs(), [e[1], e[0], e[2]]);
+        assert_eq!(tabs.active(), Some(e[0]));
+        tabs.move_active(5);
+        assert_eq!(tabs.tabs(), [e[1], e[2], e[0]]);
+        tabs.move_active(-5);
+        assert_eq!(tabs.tabs(), [e[0], e[1], e[2]]);
+        assert_eq!(tabs.active_index(), 0);
+    }
+
+    #[test]
+    fn click_selects_toggles_and_extends() {
+        let mut selection = selection(&["a", "b", "c", "d", "e"]);
+        selection.click(1, false, false);
+        assert_eq!(selected(&selection), [1]);
+        selection.click(3, false, false);
+        assert_eq!(selected(&selection), [3]);
+        // Ctrl adds and removes single rows
+        selection.click(0, true, false);
+        selection.click(4, true, false);
+        assert_eq!(selected(&selection), [0, 3, 4]);
+        selection.click(3, true, false);
+        assert_eq!(selected(&selection), [0, 4]);
+        // Shift selects from the last clicked row, replacing the selection
+        selection.click(2, false, true);
+        assert_eq!(selected(&selection), [2, 3]);
+        // Ctrl+Shift adds the range
+        selection.click(0, false, false);
+        selection.click(4, true, false);
+        selection.click(3, true, true);
+        assert_eq!(selected(&selection), [0, 3, 4]);
+        assert_eq!(selection.focused(), Some(3));
+        // rows that are not listed are ignored
+        selection.click(5, false, false);
+        assert_eq!(selected(&selection), [0, 3, 4]);
+    }
+
+    #[test]
+    fn shift_click_without_anchor_selects_the_row() {
+        let mut selection = selection(&["a", "b", "c"]);
+        selection.click(2, false, true);
+        assert_eq!(selected(&selection), [2]);
+    }
+
+    #[test]
+    fn move_focus_stays_in_bounds_and_extends() {
+        let mut selection = selection(&["a", "b", "c", "d"]);
+        // without focus, up starts at the end and down at the start
+        selection.move_focus(-1, false);
+        assert_eq!(selection.focused(), Some(3));
+        selection.move_focus(5, false);
+        assert_eq!(selection.focused(), Some(3));
+        selection.move_focus(-1, false);
+        assert_eq!(selected(&selection), [2]);
+        selection.move_focus(-1, true);
+        selection.move_focus(-1, true);
+        assert_eq!(selected(&selection), [0, 1, 2]);
+        selection.move_focus(-10, false);
+        assert_eq!(selection.focused(), Some(0));
+        assert_eq!(selected(&selection), [0]);
+
+        let mut empty = EntrySelection::default();
+        empty.move_focus(1, false);
+        assert_eq!(empty.focused(), None);
+    }
+
+    #[test]
+    fn set_rows_follows_paths() {
+        let mut selection = selection(&["a", "b", "c", "d"]);
+        selection.click(1, false, false);
+        selection.click(3, true, false);
+        selection.set_rows(["d", "new", "b"].map(PathBuf::from).to_vec());
+        assert_eq!(selected(&selection), [0, 2]);
+        assert_eq!(selection.focused(), Some(0));
+        assert_eq!(selection.focused_path(), Some(Path::new("d")));
+        // the anchor moved along with its row
+        selection.click(1, false, true);
+        assert_eq!(selected(&selection), [0, 1]);
+        // rows that are gone drop out
+        selection.set_rows(["new"].map(PathBuf::from).to_vec());
+        assert_eq!(selected(&selection), [0]);
+        assert_eq!(selection.focused(), Some(0));
+        selection.set_rows(vec![]);
+        assert_eq!(selection.focused(), None);
+        assert!(selection.targets().is_empty());
+    }
+
+    #[test]
+    fn select_all_invert_and_clear() {
+        let mut selection = selection(&["a", "b", "c"]);
+        selection.click(1, false, false);
+        selection.invert();
+        assert_eq!(selected(&selection), [0, 2]);
+        selection.select_all();
+        assert_eq!(selected(&selection), [0, 1, 2]);
+        selection.clear();
+        assert_eq!(selected(&selection), []);
+        assert_eq!(selection.focused(), Some(1));
+    }
+
+    #[test]
+    fn targets_are_the_selection_or_the_focused_row() {
+        let mut selection = selection(&["a", "b", "c"]);
+        assert!(selection.targets().is_empty());
+        selection.click(2, false, false);
+        selection.click(0, true, false);
+        assert_eq!(selection.targets(), [Path::new("a"), Path::new("c")]);
+        selection.clear();
+        assert_eq!(selection.targets(), [Path::new("a")]);
+    }
+
+    #[test]
+    fn forgotten_listings_are_requested_again() {
+        let mut listings = DirectoryListings::default();
+        let path = Path::new("/a");
+        listings.request(path.to_owned());
+        assert!(listings.is_requested(path));
+        assert!(listings.get(path).is_none());
+        listings.resolve(path, vec![]);
+        assert_eq!(listings.get(path).map(<[_]>::len), Some(0));
+
+        listings.forget(path);
+        assert!(!listings.is_requested(path));
+        // a listing that finishes loading after it was forgotten is dropped
+        listings.resolve(path, vec![]);
+        assert!(listings.get(path).is_none());
+        assert_eq!(listings.paths().count(), 0);
+    }
+}
@@ -0,0 +1,607 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::{Duration, Instant};
+
+use bevy::tasks::{IoTaskPool, Task, block_on, poll_once};
+use derive_more::Display;
+use globset::{Glob, GlobMatcher};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use regex::Regex;
+
+use crate::bridge::{ActiveTabChanged, CurrentDirectoryChanged};
+use crate::command_registry::{AddCommandExt, RegisteredCommand};
+use crate::fs::{ResolvedEntry, resolve_path};
+use crate::notifications::Notification;
+use crate::prelude::{Event, *};
+use crate::resources::{ActiveTab, CurrentDirectory};
+use crate::search::file_index::file_index_plugin;
+
+pub mod file_index;
+
+/// files larger than this are not searched for content
+const MAX_GREP_FILE_SIZE: u64 = 8 * 1024 * 1024;
+/// a NUL byte in this many leading bytes marks a file as binary
+const BINARY_PROBE_SIZE: usize = 8 * 1024;
+/// further matching lines in a file are not reported
+const MAX_LINES_PER_FILE: usize = 100;
+/// line previews are cut off after this many characters
+const MAX_LINE_PREVIEW: usize = 160;
+
+#[derive(Clone, Copy, Debug, Default, Display, Eq, PartialEq)]
+pub enum SearchMode {
+    #[default]
+    #[display("substring")]
+    Substring,
+    #[display("glob")]
+    Glob,
+    #[display("regex")]
+    Regex,
+}
+
+impl SearchMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Substring => Self::Glob,
+            Self::Glob => Self::Regex,
+            Self::Regex => Self::Substring,
+        }
+    }
+}
+
+/// What a [SearchPattern] is matched against
+#[derive(Clone, Copy, Debug, Default, Display, Eq, PartialEq)]
+pub enum SearchScope {
+    #[default]
+    #[display("names")]
+    Names,
+    #[display("contents")]
+    Contents,
+}
+
+impl SearchScope {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Names => Self::Contents,
+            Self::Contents => Self::Names,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PatternError {
+    #[error("empty search pattern")]
+    Empty,
+    #[error("glob patterns only match file names")]
+    GlobContents,
+    #[error("invalid glob: {0}")]
+    Glob(#[from] globset::Error),
+    #[error("invalid regex: {0}")]
+    Regex(#[from] regex::Error),
+}
+
+/// Matches file names (not whole paths) or lines of text
+#[derive(Clone, Debug)]
+pub enum SearchPattern {
+    /// case-insensitive unless the pattern contains upper case characters
+    Substring {
+        needle: String,
+        ignore_case: bool,
+    },
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+impl SearchPattern {
+    pub fn parse(
+        mode: SearchMode,
+        scope: SearchScope,
+        pattern: &str,
+    ) -> Result<Self, PatternError> {
+        if pattern.is_empty() {
+            return Err(PatternError::Empty);
+        }
+        Ok(match mode {
+            SearchMode::Glob if scope == SearchScope::Contents => {
+                return Err(PatternError::GlobContents);
+            }
+            SearchMode::Substring => {
+                let ignore_case = !pattern.chars().any(char::is_uppercase);
+                Self::Substring {
+                    needle: pattern.to_string(),
+                    ignore_case,
+                }
+            }
+            SearchMode::Glob => Self::Glob(Glob::new(pattern)?.compile_matcher()),
+            SearchMode::Regex => Self::Regex(Regex::new(pattern)?),
+        })
+    }
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Substring {
+                needle,
+                ignore_case: true,
+            } => name.to_lowercase().contains(needle),
+            Self::Substring { needle, .. } => name.contains(needle),
+            Self::Glob(matcher) => matcher.is_match(name),
+            Self::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Resource)]
+pub struct SearchOptions {
+    pub mode: SearchMode,
+    pub scope: SearchScope,
+    /// how many directories deep to descend, unlimited if `None`
+    pub max_depth: Option<usize>,
+    /// skip paths listed in `.gitignore` and `.ignore` files, and `.git` itself
+    pub respect_ignore_files: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            mode: default(),
+            scope: default(),
+            max_depth: None,
+            respect_ignore_files: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Event)]
+pub enum SearchCommand {
+    /// search below the current directory for a pattern, see [SearchOptions]
+    Start(String),
+    Cancel,
+    /// cancel and forget the results
+    Clear,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SearchState {
+    #[default]
+    Idle,
+    Running,
+    Done(Duration),
+    Cancelled,
+}
+
+#[derive(Clone, Debug)]
+pub struct LineMatch {
+    /// 1-based
+    pub number: usize,
+    pub text: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchHit {
+    pub entry: ResolvedEntry,
+    /// matching lines, empty when searching names
+    pub lines: Vec<LineMatch>,
+}
+
+/// Matches of the current search, in the order they were found.
+/// Searches are of the active tab, switching tabs clears them
+#[derive(Debug, Default, Resource)]
+pub struct SearchResults {
+    pub root: PathBuf,
+    pub pattern: String,
+    pub scope: SearchScope,
+    pub state: SearchState,
+    pub hits: Vec<SearchHit>,
+}
+
+impl SearchResults {
+    /// a search is running or its results are shown
+    pub fn is_active(&self) -> bool {
+        self.state != SearchState::Idle
+    }
+}
+
+/// A [Walker] running on the [IoTaskPool], streaming its hits back
+#[derive(Debug)]
+pub(crate) struct WalkTask {
+    started: Instant,
+    cancelled: Arc<AtomicBool>,
+    receiver: Mutex<mpsc::Receiver<SearchHit>>,
+    task: Task<()>,
+}
+
+impl WalkTask {
+    pub fn spawn(root: PathBuf, pattern: SearchPattern, options: SearchOptions) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let walker = Walker {
+            pattern,
+            options,
+            cancelled: cancelled.clone(),
+            sender,
+        };
+        Self {
+            started: Instant::now(),
+            cancelled,
+            receiver: Mutex::new(receiver),
+            task: IoTaskPool::get().spawn(async move {
+                walker.walk(&root);
+            }),
+        }
+    }
+    /// stops walking, hits that were already sent are still received
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    /// hits sent since the last poll, and whether the walk has finished
+    pub fn poll(&mut self) -> (Vec<SearchHit>, bool) {
+        let finished = block_on(poll_once(&mut self.task)).is_some();
+        // drain after polling so no hits sent before finishing are lost
+        let hits = self
+            .receiver
+            .lock()
+            .map(|receiver| receiver.try_iter().collect_vec())
+            .unwrap_or_default();
+        (hits, finished)
+    }
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+#[derive(Component, Debug, Deref, DerefMut)]
+struct Searcher(WalkTask);
+
+struct Walker {
+    pattern: SearchPattern,
+    options: SearchOptions,
+    cancelled: Arc<AtomicBool>,
+    sender: mpsc::Sender<SearchHit>,
+}
+
+/// Lines of a text file that match, `None` for binary or unreadable files
+fn grep_file(path: &Path, pattern: &SearchPattern) -> Option<Vec<LineMatch>> {
+    let mut file = File::open(path).ok()?;
+    if file.metadata().ok()?.len() > MAX_GREP_FILE_SIZE {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    if bytes.iter().take(BINARY_PROBE_SIZE).any(|b| *b == 0) {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    let lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| pattern.matches(line))
+        .take(MAX_LINES_PER_FILE)
+        .map(|(index, line)| LineMatch {
+            number: index + 1,
+            text: line.trim().chars().take(MAX_LINE_PREVIEW).collect(),
+        })
+        .collect_vec();
+    Some(lines)
+}
+
+impl Walker {
+    /// Sends the matching entries below `root` until cancelled or the hits are no longer wanted
+    fn walk(&self, root: &Path) {
+        let respect_ignore_files = self.options.respect_ignore_files;
+        WalkBuilder::new(root)
+            .standard_filters(respect_ignore_files)
+            // hidden files are searched too, only ignore files leave paths out
+            .hidden(false)
+            .require_git(false)
+            .filter_entry(move |entry| !respect_ignore_files || entry.file_name() != ".git")
+            // the entries of `root` are at depth 1
+            .max_depth(self.options.max_depth.map(|depth| depth + 1))
+            .threads(if cfg!(feature = "async") { 0 } else { 1 })
+            .build_parallel()
+            .run(|| Box::new(|entry| self.visit(entry)));
+    }
+
+    fn visit(&self, entry: Result<DirEntry, ignore::Error>) -> WalkState {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return WalkState::Quit;
+        }
+        // unreadable entries are skipped, and so is `root` itself
+        let Ok(entry) = entry else {
+            return WalkState::Continue;
+        };
+        if entry.depth() == 0 {
+            return WalkState::Continue;
+        }
+        let lines = match self.options.scope {
+            SearchScope::Names => {
+                if !self.pattern.matches(&entry.file_name().to_string_lossy()) {
+                    return WalkState::Continue;
+                }
+                Vec::new()
+            }
+            SearchScope::Contents => {
+                if !entry.file_type().is_some_and(|t| t.is_file()) {
+                    return WalkState::Continue;
+                }
+                match grep_file(entry.path(), &self.pattern) {
+                    Some(lines) if !lines.is_empty() => lines,
+                    _ => return WalkState::Continue,
+                }
+            }
+        };
+        let Some(entry) = resolve_path(entry.into_path()) else {
+            return WalkState::Continue;
+        };
+        match self.sender.send(SearchHit { entry, lines }) {
+            Ok(()) => WalkState::Continue,
+            // results are no longer wanted
+            Err(_) => WalkState::Quit,
+        }
+    }
+}
+
+fn cancel_searches(commands: &mut Commands, searchers: &Query<(Entity, &Searcher)>) {
+    for (e, searcher) in searchers {
+        searcher.cancel();
+        commands.entity(e).despawn();
+    }
+}
+
+fn handle_search_commands(
+    mut commands: Commands,
+    mut search_commands: EventReader<SearchCommand>,
+    mut notifications: EventWriter<Notification>,
+    mut results: ResMut<SearchResults>,
+    options: Res<SearchOptions>,
+    current_directory: ActiveTab<&CurrentDirectory>,
+    searchers: Query<(Entity, &Searcher)>,
+) {
+    for command in search_commands.read() {
+        match command {
+            SearchCommand::Start(pattern) => {
+                let pattern_text = pattern;
+                let pattern = match SearchPattern::parse(options.mode, options.scope, pattern) {
+                    Ok(pattern) => pattern,
+                    Err(error) => {
+                        notifications.write(Notification::warning(error.to_string()));
+                        continue;
+                    }
+                };
+                let Some(current_directory) = current_directory.get() else {
+                    continue;
+                };
+                cancel_searches(&mut commands, &searchers);
+
+                let root: PathBuf = current_directory.to_path_buf();
+                info!(
+                    "search {} of {root:?} for {} {pattern_text:?}",
+                    options.scope, options.mode
+                );
+                commands.spawn(Searcher(WalkTask::spawn(
+                    root.clone(),
+                    pattern,
+                    options.clone(),
+                )));
+                *results = SearchResults {
+                    root,
+                    pattern: pattern_text.clone(),
+                    scope: options.scope,
+                    state: SearchState::Running,
+                    hits: default(),
+                };
+            }
+            SearchCommand::Cancel => {
+                cancel_searches(&mut commands, &searchers);
+                if results.state == SearchState::Running {
+                    results.state = SearchState::Cancelled;
+                }
+            }
+            SearchCommand::Clear => {
+                cancel_searches(&mut commands, &searchers);
+                *results = default();
+            }
+        }
+    }
+}
+
+fn poll_searchers(
+    mut commands: Commands,
+    mut results: ResMut<SearchResults>,
+    searchers: Query<(Entity, &mut Searcher)>,
+) {
+    for (e, mut searcher) in searchers {
+        let (hits, finished) = searcher.poll();
+        if !hits.is_empty() {
+            results.hits.extend(hits);
+        }
+        if finished {
+            results.state = SearchState::Done(searcher.elapsed());
+            commands.entity(e).despawn();
+        }
+    }
+}
+
+fn clear_search_results(mut search_commands: EventWriter<SearchCommand>) {
+    search_commands.write(SearchCommand::Clear);
+}
+
+pub fn search_plugin(app: &mut App) {
+    app.add_plugins(file_index_plugin)
+        .init_resource::<SearchOptions>()
+        .init_resource::<SearchResults>()
+        .add_event::<SearchCommand>()
+        .add_command(RegisteredCommand::with_argument(
+            "Search",
+            "a pattern to search for below the current directory",
+            |commands, pattern| {
+                commands.send_event(SearchCommand::Start(pattern.to_owned()));
+            },
+        ))
+        .add_command(RegisteredCommand::send_event(
+            "Cancel search",
+            SearchCommand::Cancel,
+        ))
+        .add_command(RegisteredCommand::send_event(
+            "Clear search results",
+            SearchCommand::Clear,
+        ))
+        .add_systems(
+            FixedUpdate,
+            (
+                // results are relative to the directory they were searched in
+                clear_search_results
+                    .run_if(on_event::<CurrentDirectoryChanged>.or(on_event::<ActiveTabChanged>)),
+                handle_search_commands.run_if(on_event::<SearchCommand>),
+                poll_searchers,
+            )
+                .chain(),
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn fake_tree(name: &str, files: &[(&str, &[u8])]) -> TestDir {
+        let root = TestDir::new(name);
+        for (path, contents) in files {
+            root.write(path, contents);
+        }
+        root
+    }
+
+    /// Paths of the hits relative to `root`, sorted
+    fn walk(root: &Path, pattern: SearchPattern, options: SearchOptions) -> Vec<String> {
+        let (sender, receiver) = mpsc::channel();
+        let walker = Walker {
+            pattern,
+            options,
+            cancelled: default(),
+            sender,
+        };
+        walker.walk(root);
+        drop(walker);
+        receiver
+            .iter()
+            .map(|hit| {
+                let path = hit.entry.path().strip_prefix(root).unwrap();
+                path.to_string_lossy().into_owned()
+            })
+            .sorted()
+            .collect()
+    }
+
+    fn names(pattern: &str) -> SearchPattern {
+        SearchPattern::parse(SearchMode::Substring, SearchScope::Names, pattern).unwrap()
+    }
+
+    #[test]
+    fn substring_is_case_insensitive_unless_upper_case() {
+        let lower = names("readme");
+        assert!(lower.matches("README.md"));
+        assert!(lower.matches("readme.txt"));
+        let upper = names("README");
+        assert!(upper.matches("README.md"));
+        assert!(!upper.matches("readme.txt"));
+    }
+
+    #[test]
+    fn glob_matches_whole_name() {
+        let glob = SearchPattern::parse(SearchMode::Glob, SearchScope::Names, "*.rs").unwrap();
+        assert!(glob.matches("main.rs"));
+        assert!(!glob.matches("main.rs.bak"));
+        assert!(!glob.matches("rs"));
+    }
+
+    #[test]
+    fn regex_matches_anywhere() {
+        let regex =
+            SearchPattern::parse(SearchMode::Regex, SearchScope::Contents, r"fn \w+\(").unwrap();
+        assert!(regex.matches("pub fn parse(mode: SearchMode)"));
+        assert!(!regex.matches("fn"));
+        // not special in other modes
+        assert!(!names(r"fn \w+\(").matches("pub fn parse("));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        let parse = |mode, scope, pattern| SearchPattern::parse(mode, scope, pattern).unwrap_err();
+        use SearchMode::*;
+        use SearchScope::*;
+        assert!(matches!(parse(Substring, Names, ""), PatternError::Empty));
+        assert!(matches!(
+            parse(Glob, Contents, "*"),
+            PatternError::GlobContents
+        ));
+        assert!(matches!(parse(Glob, Names, "a[b"), PatternError::Glob(_)));
+        assert!(matches!(parse(Regex, Names, "a(b"), PatternError::Regex(_)));
+    }
+
+    #[test]
+    fn grep_file_skips_binary_files() {
+        let root = fake_tree(
+            "grep",
+            &[
+                ("text.txt", b"first line\n  a needle  \nlast needle\n"),
+                ("binary.bin", b"needle\0needle\n"),
+            ],
+        );
+        let lines = grep_file(&root.join("text.txt"), &names("needle")).unwrap();
+        let lines = lines
+            .iter()
+            .map(|line| (line.number, line.text.as_str()))
+            .collect_vec();
+        assert_eq!(lines, [(2, "a needle"), (3, "last needle")]);
+        assert!(grep_file(&root.join("binary.bin"), &names("needle")).is_none());
+    }
+
+    #[test]
+    fn walker_respects_ignore_files_and_max_depth() {
+        let root = fake_tree(
+            "walk",
+            &[
+                (".gitignore", b"*.log\nbuild/\n"),
+                ("a.txt", b""),
+                ("a.log", b""),
+                ("build/b.txt", b""),
+                (".git/c.txt", b""),
+                ("sub/.ignore", b"!keep.log\n"),
+                ("sub/keep.log", b""),
+                ("sub/d.txt", b""),
+                ("sub/deeper/e.txt", b""),
+            ],
+        );
+        let pattern =
+            SearchPattern::parse(SearchMode::Regex, SearchScope::Names, r"\.(txt|log)$").unwrap();
+        assert_eq!(
+            walk(&root, pattern.clone(), default()),
+            ["a.txt", "sub/d.txt", "sub/deeper/e.txt", "sub/keep.log"]
+        );
+        let options = SearchOptions {
+            max_depth: Some(1),
+            ..default()
+        };
+        assert_eq!(
+            walk(&root, pattern.clone(), options),
+            ["a.txt", "sub/d.txt", "sub/keep.log"]
+        );
+        let options = SearchOptions {
+            respect_ignore_files: false,
+            ..default()
+        };
+        assert_eq!(
+            walk(&root, pattern, options),
+            [
+                ".git/c.txt",
+                "a.log",
+                "a.txt",
+                "build/b.txt",
+                "sub/d.txt",
+                "sub/deeper/e.txt",
+                "sub/keep.log"
+            ]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use crate::bridge::{ActiveTabChanged, CurrentDirectoryChanged};
+use crate::fs::ResolvedEntry;
+use crate::prelude::*;
+use crate::resources::{ActiveTab, CurrentDirectory};
+use crate::search::{SearchOptions, SearchPattern, SearchScope, WalkTask};
+
+/// indexing stops here, so huge trees do not eat all memory
+const MAX_INDEXED_PATHS: usize = 200_000;
+
+#[derive(Clone, Debug)]
+pub struct IndexedPath {
+    /// relative to the root of the index, used for matching
+    pub relative: String,
+    pub entry: ResolvedEntry,
+}
+
+/// Every path below the current directory of the active tab, for quick fuzzy lookups
+#[derive(Debug, Default, Resource)]
+pub struct FileIndex {
+    pub root: PathBuf,
+    pub paths: Vec<IndexedPath>,
+    /// all paths are indexed
+    pub complete: bool,
+}
+
+#[derive(Component, Debug, Deref, DerefMut)]
+pub(crate) struct Indexer(WalkTask);
+
+/// Scores how well `query` matches `candidate` as a subsequence, higher is better.
+/// Consecutive characters and characters at the start of a path segment or word count extra.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let mut score = 0;
+    // byte index right after the previously matched character
+    let mut previous_match_end: Option<usize> = None;
+    let mut candidate_chars = candidate.char_indices();
+    let mut previous_char = None;
+    for query_char in query.chars().filter(|c| !c.is_whitespace()) {
+        let query_char = query_char.to_ascii_lowercase();
+        loop {
+            let (index, c) = candidate_chars.next()?;
+            let is_boundary = previous_char.is_none_or(|p: char| {
+                matches!(p, '/' | '_' | '-' | '.' | ' ') || (p.is_lowercase() && c.is_uppercase())
+            });
+            previous_char = Some(c);
+            if c.to_ascii_lowercase() != query_char {
+                continue;
+            }
+            score += 1;
+            if is_boundary {
+                score += 8;
+            }
+            if previous_match_end == Some(index) {
+                score += 5;
+            }
+            previous_match_end = Some(index + c.len_utf8());
+            break;
+        }
+    }
+    // prefer matches in the file name, and shorter paths
+    let name_start = candidate.rfind('/').map_or(0, |i| i + 1);
+    if previous_match_end.is_some_and(|end| end > name_start) {
+        score += 10;
+    }
+    Some(score * 100 - candidate.len() as i64)
+}
+
+fn cancel_indexing(commands: &mut Commands, indexers: &Query<(Entity, &Indexer)>) {
+    for (e, indexer) in indexers {
+        indexer.cancel();
+        commands.entity(e).despawn();
+    }
+}
+
+/// Indexes the current directory unless that is done or under way, run when quick open opens
+pub(crate) fn index_current_directory(
+    mut commands: Commands,
+    mut index: ResMut<FileIndex>,
+    current_directory: ActiveTab<&CurrentDirectory>,
+    options: Res<SearchOptions>,
+    indexers: Query<(Entity, &Indexer)>,
+) {
+    let Some(current_directory) = current_directory.get() else {
+        return;
+    };
+    if index.root == **current_directory && (index.complete || !indexers.is_empty()) {
+        return;
+    }
+    cancel_indexing(&mut commands, &indexers);
+    let root: PathBuf = current_directory.to_path_buf();
+    debug!("indexing {root:?}");
+    let everything = SearchPattern::Substring {
+        needle: default(),
+        ignore_case: false,
+    };
+    let options = SearchOptions {
+        scope: SearchScope::Names,
+        ..options.clone()
+    };
+    commands.spawn(Indexer(WalkTask::spawn(root.clone(), everything, options)));
+    *index = FileIndex { root, ..default() };
+}
+
+/// The index is of another directory now, it is built again when quick open is next opened
+fn forget_index(
+    mut commands: Commands,
+    mut index: ResMut<FileIndex>,
+    indexers: Query<(Entity, &Indexer)>,
+) {
+    cancel_indexing(&mut commands, &indexers);
+    *index = default();
+}
+
+fn poll_indexers(
+    mut commands: Commands,
+    mut index: ResMut<FileIndex>,
+    indexers: Query<(Entity, &mut Indexer)>,
+) {
+    for (e, mut indexer) in indexers {
+        let (hits, finished) = indexer.poll();
+        if !hits.is_empty() {
+            let root = index.root.clone();
+            index.paths.extend(hits.into_iter().map(|hit| {
+                IndexedPath {
+                    relative: hit
+                        .entry
+                        .path()
+                        .strip_prefix(&root)
+                        .unwrap_or(hit.entry.path())
+                        .to_string_lossy()
+                        .into_owned(),
+                    entry: hit.entry,
+                }
+            }));
+        }
+        if index.paths.len() >= MAX_INDEXED_PATHS {
+            warn!(
+                "stopped indexing {:?} after {MAX_INDEXED_PATHS} paths",
+                index.root
+            );
+            indexer.cancel();
+            commands.entity(e).despawn();
+        } else if finished {
+            debug!(
+                "indexed {} paths in {:?}",
+                index.paths.len(),
+                indexer.elapsed()
+            );
+            index.complete = true;
+            commands.entity(e).despawn();
+        }
+    }
+}
+
+pub fn file_index_plugin(app: &mut App) {
+    app.init_resource::<FileIndex>().add_systems(
+        FixedUpdate,
+        (
+            forget_index
+                .run_if(on_event::<CurrentDirectoryChanged>.or(on_event::<ActiveTabChanged>)),
+            poll_indexers,
+        )
+            .chain(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `candidates` sorted by their score for `query`, best first, without those not matching
+    fn ranked<'a>(query: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+        candidates
+            .iter()
+            .filter_map(|candidate| Some((fuzzy_score(query, candidate)?, *candidate)))
+            .sorted_by_key(|(score, _)| -score)
+            .map(|(_, candidate)| candidate)
+            .collect()
+    }
+
+    #[test]
+    fn matches_subsequences_ignoring_case_and_whitespace() {
+        assert!(fuzzy_score("mtr", "src/main_tab.rs").is_some());
+        assert!(fuzzy_score("MainTab", "src/main_tab.rs").is_some());
+        assert!(fuzzy_score("main tab", "src/main_tab.rs").is_some());
+        assert!(fuzzy_score("", "anything").is_some());
+        // in order only
+        assert_eq!(fuzzy_score("rm", "main.rs"), None);
+        assert_eq!(fuzzy_score("mainx", "main.rs"), None);
+    }
+
+    #[test]
+    fn prefers_consecutive_characters_at_boundaries() {
+        assert_eq!(
+            ranked(
+                "tab",
+                &["src/stab.rs", "src/ui/main_tab.rs", "src/table_view.rs"]
+            ),
+            ["src/table_view.rs", "src/ui/main_tab.rs", "src/stab.rs"]
+        );
+        assert_eq!(
+            ranked("ti", &["src/textinput.rs", "src/TextInput.rs"]),
+            ["src/TextInput.rs", "src/textinput.rs"]
+        );
+    }
+
+    #[test]
+    fn prefers_file_names_and_shorter_paths() {
+        assert_eq!(
+            ranked("lib", &["lib/src/a.rs", "src/lib.rs"]),
+            ["src/lib.rs", "lib/src/a.rs"]
+        );
+        assert_eq!(
+            ranked("main", &["src/deeper/path/main.rs", "src/main.rs"]),
+            ["src/main.rs", "src/deeper/path/main.rs"]
+        );
+    }
+}
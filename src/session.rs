@@ -0,0 +1,197 @@
+use std::{env, fs, io};
+
+use bevy::window::{PrimaryWindow, WindowCloseRequested, WindowMode, close_when_requested};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::bridge::ActiveTabChanged;
+use crate::navigation::LocationHistory;
+use crate::prelude::*;
+use crate::resources::{CurrentDirectory, ExplorerTab, ExplorerTabs, PreviewPath};
+use crate::settings::{Settings, load_settings};
+use crate::ui::AppTab;
+use crate::ui::splitter::PaneSizes;
+use crate::window_settings::WindowSize;
+use crate::xdg;
+
+/// in `$XDG_STATE_HOME/corvus`, written on exit
+const SESSION_FILE: &str = "session.ron";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct TabSession {
+    directory: PathBuf,
+    back: Vec<PathBuf>,
+    next: Vec<PathBuf>,
+    preview: Option<PathBuf>,
+}
+
+/// Size and position of the window when it was last windowed
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+struct WindowGeometry {
+    size: WindowSize,
+    position: Option<[i32; 2]>,
+}
+
+/// What is restored on start
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Session {
+    tabs: Vec<TabSession>,
+    /// index into `tabs`
+    active: usize,
+    app_tab: AppTab,
+    window: Option<WindowGeometry>,
+    pane_sizes: PaneSizes,
+}
+
+/// The tab of the restored session, shown once the ui is loaded
+#[derive(Debug, Resource)]
+struct RestoredAppTab(AppTab);
+
+fn session_path() -> Option<PathBuf> {
+    xdg::state_dir().map(|dir| dir.join(SESSION_FILE))
+}
+
+fn read_session() -> Option<Session> {
+    let path = session_path()?;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            warn!("could not read {path:?}: {e}");
+            return None;
+        }
+    };
+    ron::from_str(&text)
+        .inspect_err(|e| warn!("invalid {path:?}: {e}"))
+        .ok()
+}
+
+/// The path, or its nearest ancestor that still exists
+fn existing(path: PathBuf) -> PathBuf {
+    CurrentDirectory::from(path).to_path_buf()
+}
+
+fn restore_session(
+    mut commands: Commands,
+    mut tabs: ResMut<ExplorerTabs>,
+    settings: Res<Settings>,
+    window: Option<Single<&mut Window, With<PrimaryWindow>>>,
+    mut active_tab_changed: EventWriter<ActiveTabChanged>,
+) {
+    let Some(session) = read_session() else {
+        return;
+    };
+    if let (Some(mut window), Some(geometry)) = (window, session.window) {
+        // a size chosen in the settings wins over the last one
+        if settings.window.size.is_none() {
+            let size = geometry.size.0;
+            window.resolution.set_physical_resolution(size.x, size.y);
+        }
+        if let Some([x, y]) = geometry.position {
+            window.position = WindowPosition::At(IVec2::new(x, y));
+        }
+    }
+    commands.insert_resource(RestoredAppTab(session.app_tab));
+    commands.insert_resource(session.pane_sizes);
+
+    let mut directories = vec![];
+    for tab in session.tabs {
+        let directory = CurrentDirectory::from(tab.directory);
+        let history = LocationHistory::new(
+            tab.back.into_iter().map(existing).collect(),
+            tab.next.into_iter().map(existing).collect(),
+        );
+        // a preview of an ancestor would show something else
+        let preview = tab.preview.filter(|path| path.exists());
+        directories.push(directory.to_path_buf());
+        let e = commands
+            .spawn((ExplorerTab, directory, history, PreviewPath::from(preview)))
+            .id();
+        tabs.insert(None, e);
+    }
+    let index = session.active.min(directories.len().saturating_sub(1));
+    let (Some(&active), Some(directory)) = (tabs.tabs().get(index), directories.get(index)) else {
+        return;
+    };
+    tabs.activate(active);
+    if let Err(e) = env::set_current_dir(directory) {
+        warn!("cannot change to {directory:?}: {e}");
+    }
+    active_tab_changed.write_default();
+}
+
+fn restore_app_tab(
+    mut commands: Commands,
+    restored: Option<Res<RestoredAppTab>>,
+    mut next_app_tab: ResMut<NextState<AppTab>>,
+) {
+    if let Some(restored) = restored {
+        next_app_tab.set(restored.0);
+        commands.remove_resource::<RestoredAppTab>();
+    }
+}
+
+fn save_session(
+    tabs: Res<ExplorerTabs>,
+    tab_states: Query<(&CurrentDirectory, &LocationHistory, &PreviewPath), With<ExplorerTab>>,
+    app_tab: Option<Res<State<AppTab>>>,
+    pane_sizes: Res<PaneSizes>,
+    window: Option<Single<&Window, With<PrimaryWindow>>>,
+) {
+    let Some(path) = session_path() else {
+        return;
+    };
+    let window = match window {
+        Some(window) if window.mode == WindowMode::Windowed => Some(WindowGeometry {
+            size: WindowSize(window.physical_size()),
+            position: match window.position {
+                WindowPosition::At(position) => Some(position.to_array()),
+                _ => None,
+            },
+        }),
+        // keep what it was before going fullscreen
+        _ => read_session().and_then(|session| session.window),
+    };
+    let session = Session {
+        tabs: tabs
+            .tabs()
+            .iter()
+            .filter_map(|tab| tab_states.get(*tab).ok())
+            .map(|(directory, history, preview)| TabSession {
+                directory: directory.to_path_buf(),
+                back: history.back().to_vec(),
+                next: history.next().to_vec(),
+                preview: (**preview).clone(),
+            })
+            .collect(),
+        active: tabs.active_index(),
+        app_tab: app_tab.map(|app_tab| *app_tab.get()).unwrap_or_default(),
+        window,
+        pane_sizes: pane_sizes.clone(),
+    };
+    let result = ron::ser::to_string_pretty(&session, PrettyConfig::default())
+        .map_err(io::Error::other)
+        .and_then(|text| {
+            path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, text))
+        });
+    if let Err(e) = result {
+        warn!("could not save the session to {path:?}: {e}");
+    }
+}
+
+pub fn session_plugin(app: &mut App) {
+    app.add_systems(PreStartup, restore_session.after(load_settings))
+        .add_systems(OnEnter(CobwebLoadState::Done), restore_app_tab)
+        .add_systems(
+            Update,
+            // while the window is still there
+            save_session
+                .run_if(on_event::<WindowCloseRequested>)
+                .before(close_when_requested),
+        )
+        .add_systems(Last, save_session.run_if(on_event::<AppExit>));
+}
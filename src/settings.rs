@@ -0,0 +1,262 @@
+use std::time::{Duration, SystemTime};
+use std::{fs, io};
+
+use bevy::time::common_conditions::on_timer;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::notifications::Notification;
+use crate::prelude::*;
+use crate::resources::{LocationBar, OverviewMode, PanelLayout};
+use crate::window_settings::WindowSettings;
+use crate::xdg;
+
+/// in `$XDG_CONFIG_HOME/corvus`
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// Bumped with a step in `migrate` whenever a setting is renamed or changes meaning
+const SETTINGS_VERSION: u32 = 1;
+
+/// How often the file is checked for edits made outside of corvus
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Everything the settings tab changes, saved whenever it changes
+#[derive(Clone, Debug, Deserialize, PartialEq, Resource, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    /// `0` while there is no settings file yet
+    version: u32,
+    pub panel_layout: PanelLayout,
+    pub location_bar: LocationBar,
+    pub overview_mode: OverviewMode,
+    /// shown next to the overview
+    pub directory_tree: bool,
+    pub window: WindowSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            panel_layout: default(),
+            location_bar: default(),
+            overview_mode: default(),
+            directory_tree: true,
+            window: default(),
+        }
+    }
+}
+
+impl Settings {
+    /// An empty or missing file is version `0`, `true` when migrated
+    fn parse(text: &str) -> Result<(Self, bool), ron::error::SpannedError> {
+        let mut settings = match text.trim().is_empty() {
+            true => Self {
+                version: 0,
+                ..default()
+            },
+            false => ron::from_str::<Self>(text)?,
+        };
+        let migrated = settings.version < SETTINGS_VERSION;
+        settings.migrate();
+        Ok((settings, migrated))
+    }
+
+    /// Brings settings of older versions up to date, one version at a time
+    fn migrate(&mut self) {
+        if self.version > SETTINGS_VERSION {
+            warn!(
+                "{SETTINGS_FILE} is version {}, newer than {SETTINGS_VERSION}, unknown settings are dropped",
+                self.version
+            );
+        }
+        while self.version < SETTINGS_VERSION {
+            match self.version {
+                // no settings file yet, nothing to carry over
+                0 => {}
+                _ => unreachable!(),
+            }
+            self.version += 1;
+        }
+    }
+
+    fn to_text(&self) -> String {
+        ron::ser::to_string_pretty(self, PrettyConfig::default()).unwrap_or_else(|e| {
+            error!("cannot serialize settings: {e}");
+            String::new()
+        })
+    }
+}
+
+/// The settings file as last read or written, edits are only picked up when it changes
+#[derive(Debug, Default, Resource)]
+pub(crate) struct SettingsFile {
+    modified: Option<SystemTime>,
+    /// What saving would write, `None` until the file was read once.
+    /// Files edited by hand are only rewritten when the settings change
+    text: Option<String>,
+    /// The file could not be parsed, it is replaced once the settings are changed in corvus
+    parse_failed: bool,
+}
+
+fn settings_path() -> Option<PathBuf> {
+    xdg::config_dir().map(|dir| dir.join(SETTINGS_FILE))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Reads the settings file if it changed since it was last seen
+pub(crate) fn load_settings(
+    mut settings: ResMut<Settings>,
+    mut file: ResMut<SettingsFile>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(path) = settings_path() else {
+        return;
+    };
+    let modified = modified(&path);
+    if modified.is_some() && modified == file.modified {
+        return;
+    }
+    file.modified = modified;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            notifications.write(Notification::warning(format!(
+                "Cannot read {}: {e}",
+                path.display()
+            )));
+            return;
+        }
+    };
+    if file.text.as_ref() == Some(&text) {
+        return;
+    }
+    match Settings::parse(&text) {
+        Ok((parsed, migrated)) => {
+            file.text = Some(match migrated {
+                true => text,
+                false => parsed.to_text(),
+            });
+            file.parse_failed = false;
+            settings.set_if_neq(parsed);
+        }
+        Err(e) => {
+            notifications.write(Notification::warning(format!(
+                "Invalid {}: {e}",
+                path.display()
+            )));
+            // keep the file until the settings are changed, it may be fixed by hand
+            file.text = Some(settings.to_text());
+            file.parse_failed = true;
+        }
+    }
+}
+
+fn save_settings(
+    settings: Res<Settings>,
+    mut file: ResMut<SettingsFile>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(path) = settings_path() else {
+        return;
+    };
+    let text = settings.to_text();
+    if text.is_empty() || file.text.as_ref() == Some(&text) {
+        return;
+    }
+    match path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, &text))
+    {
+        Ok(()) => {
+            if file.parse_failed {
+                notifications.write(Notification::info(format!(
+                    "Replaced the invalid {}",
+                    path.display()
+                )));
+                file.parse_failed = false;
+            }
+            file.modified = modified(&path);
+            file.text = Some(text);
+        }
+        Err(e) => {
+            notifications.write(Notification::warning(format!(
+                "Cannot save the settings: {e}"
+            )));
+        }
+    }
+}
+
+pub fn settings_plugin(app: &mut App) {
+    app.init_resource::<Settings>()
+        .init_resource::<SettingsFile>()
+        .add_systems(PreStartup, load_settings)
+        .add_systems(
+            Update,
+            (
+                load_settings.run_if(on_timer(WATCH_INTERVAL)),
+                save_settings
+                    .run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+            )
+                .chain(),
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_file_is_migrated_from_version_0() {
+        let (settings, migrated) = Settings::parse(" \n").unwrap();
+        assert!(migrated);
+        assert_eq!(settings, Settings::default());
+        assert_eq!(settings.version, SETTINGS_VERSION);
+    }
+
+    #[test]
+    fn current_file_round_trips() {
+        let settings = Settings {
+            panel_layout: PanelLayout::Vertical,
+            directory_tree: false,
+            ..default()
+        };
+        let (parsed, migrated) = Settings::parse(&settings.to_text()).unwrap();
+        assert!(!migrated);
+        assert_eq!(parsed, settings);
+    }
+
+    #[test]
+    fn missing_settings_are_defaults() {
+        let (settings, migrated) = Settings::parse("(directory_tree: false)").unwrap();
+        assert!(!migrated);
+        assert_eq!(
+            settings,
+            Settings {
+                directory_tree: false,
+                ..default()
+            }
+        );
+    }
+
+    #[test]
+    fn newer_version_is_kept() {
+        let text = format!("(version: {})", SETTINGS_VERSION + 1);
+        let (settings, migrated) = Settings::parse(&text).unwrap();
+        assert!(!migrated);
+        assert_eq!(settings.version, SETTINGS_VERSION + 1);
+    }
+
+    #[test]
+    fn invalid_file_is_an_error() {
+        assert!(Settings::parse("(panel_layout: diagonal)").is_err());
+        assert!(Settings::parse("(version: 1").is_err());
+    }
+}
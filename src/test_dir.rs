@@ -0,0 +1,39 @@
+use std::ops::Deref;
+use std::{env, fs, process};
+
+use crate::prelude::*;
+
+/// A fresh directory for a test, removed again when dropped
+pub(crate) struct TestDir(PathBuf);
+
+impl TestDir {
+    /// `name` keeps tests that run at the same time apart
+    pub fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("corvus-test-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    /// Writes `contents` to `relative`, creating its parent directories
+    pub fn write(&self, relative: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.0.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -1,17 +1,57 @@
 use std::time::Duration;
 
-use crate::bridge::{CurrentDirectoryChanged, DirectoryChangeRequest};
+use bevy::time::common_conditions::on_timer;
+use serde::{Deserialize, Serialize};
+
+use crate::bridge::{ActiveTabChanged, CurrentDirectoryChanged, DirectoryChangeRequest};
+use crate::command_registry::{AddCommandExt, KeyBinding, RegisteredCommand};
+use crate::navigation::LocationHistory;
+use crate::notifications::Notification;
+use crate::places::UserDir;
 use crate::prelude::*;
-use crate::resources::{CurrentDirectory, DirectoryEntries, PanelLayout};
+use crate::resources::{ActiveTab, CurrentDirectory, DirectoryEntries, EntrySelection, PreviewLine,
+                       PreviewPath};
+use crate::search::SearchResults;
 use crate::traits::{ChangeTabExt, PathChecksExt};
+use crate::ui::bookmarks_tab::bookmarks_tab_plugin;
+use crate::ui::command_palette::command_palette_plugin;
+use crate::ui::context_menu::context_menu_plugin;
+use crate::ui::history::history_plugin;
 use crate::ui::loading_screen::loading_screen_plugin;
-use crate::ui::ui_events::ViewStateReset;
+use crate::ui::log_tab::log_tab_plugin;
+use crate::ui::main_tab::columns::columns_plugin;
+use crate::ui::main_tab::directory_tree::directory_tree_plugin;
+use crate::ui::main_tab::dual_pane::dual_pane_plugin;
+use crate::ui::main_tab::entry_actions::entry_actions_plugin;
+use crate::ui::main_tab::explorer_tabs::explorer_tabs_plugin;
+use crate::ui::main_tab::main_tab_plugin;
+use crate::ui::main_tab::places::places_plugin;
+use crate::ui::main_tab::preview::preview_plugin;
+use crate::ui::open_with::open_with_plugin;
+use crate::ui::quick_open::quick_open_plugin;
+use crate::ui::settings_tab::settings_tab_plugin;
+use crate::ui::splitter::splitter_plugin;
+use crate::ui::text_input::text_input_plugin;
+use crate::ui::toasts::toasts_plugin;
+use crate::ui::ui_events::{ToggleNotificationLog, UpdateSelectionStatus, ViewStateReset};
 use crate::ui::view_state::{ViewState, view_state_plugin};
-use crate::{LocationHistory, PreviewPath};
+use crate::xdg;
 
+mod bookmarks_tab;
+#[cfg(debug_assertions)]
+mod cobweb_warnings;
+mod command_palette;
+mod context_menu;
+mod history;
 mod loading_screen;
+mod log_tab;
 mod main_tab;
+mod open_with;
+mod quick_open;
 mod settings_tab;
+pub(crate) mod splitter;
+mod text_input;
+mod toasts;
 mod ui_events;
 mod view_state;
 
@@ -47,24 +87,53 @@ enum NavigationButton {
     Next,
     Up,
     Reload,
+    Bookmark,
+    Home,
+    Root,
+    Places,
 }
 
-#[derive(Clone, Component, Copy, Debug, Default, Eq, Hash, PartialEq, SubStates)]
+#[derive(
+    Clone, Component, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize, SubStates,
+)]
 #[source(CobwebLoadState = CobwebLoadState::Done)]
 pub enum AppTab {
     #[default]
     Main,
     Settings,
+    Log,
+    Bookmarks,
+}
+
+impl AppTab {
+    /// in the order of the tab buttons
+    const ALL: [AppTab; 4] = [
+        AppTab::Main,
+        AppTab::Settings,
+        AppTab::Log,
+        AppTab::Bookmarks,
+    ];
 }
 
+/// The radio button showing a tab
+#[derive(Clone, Component, Copy, Debug)]
+struct TabButton(AppTab);
+
 #[derive(Clone, Component, Debug, PartialEq)]
 pub(crate) enum ExplorerCommand {
     Reload,
     SetPreview(Option<PathBuf>),
+    /// preview a file with one of its lines highlighted, 1-based
+    PreviewLine(PathBuf, usize),
     SetDirectory(PathBuf),
     HistoryBack,
     HistoryNext,
+    /// back when negative, forward when positive
+    HistoryJump(isize),
     GotoParent,
+    GotoHome,
+    GotoRoot,
+    GotoUserDir(UserDir),
 }
 
 #[derive(Clone, Component, Debug, PartialEq)]
@@ -81,22 +150,56 @@ fn setup_tab_buttons<'a>(
     sh: &mut SceneHandle<'a, UiBuilder<'a, Entity>>,
 ) -> std::result::Result<(), IgnoredError> {
     sh.get("main")
+        .insert(TabButton(AppTab::Main))
         .on_select(|mut commands: Commands| {
             // TODO: something useful
             commands.change_tab(AppTab::Main);
         })
         .update(
-            // select main tab by default
-            move |id: TargetId, mut commands: Commands| {
-                commands.react().entity_event(*id, Select);
+            // select the button of the current tab, main by default
+            |_: TargetId, mut commands: Commands, active_tab: Res<State<AppTab>>| {
+                commands.run_system_cached_with(select_tab, *active_tab.get());
             },
         );
-    sh.get("settings").on_select(|mut commands: Commands| {
-        commands.change_tab(AppTab::Settings);
-    });
+    sh.get("settings")
+        .insert(TabButton(AppTab::Settings))
+        .on_select(|mut commands: Commands| {
+            commands.change_tab(AppTab::Settings);
+        });
+    sh.get("log")
+        .insert(TabButton(AppTab::Log))
+        .on_select(|mut commands: Commands| {
+            commands.change_tab(AppTab::Log);
+        });
+    sh.get("bookmarks")
+        .insert(TabButton(AppTab::Bookmarks))
+        .on_select(|mut commands: Commands| {
+            commands.change_tab(AppTab::Bookmarks);
+        });
     DONE
 }
 
+/// Selects the button of `tab`, which changes the tab like a click does
+fn select_tab(In(tab): In<AppTab>, mut commands: Commands, buttons: Query<(Entity, &TabButton)>) {
+    if let Some((e, _)) = buttons.iter().find(|(_, button)| button.0 == tab) {
+        commands.react().entity_event(e, Select);
+    }
+}
+
+/// Selects the tab `step` tabs away from the current one, wrapping around
+fn cycle_tab(In(step): In<isize>, mut commands: Commands, active_tab: Option<Res<State<AppTab>>>) {
+    let Some(active_tab) = active_tab else {
+        return;
+    };
+    let count = AppTab::ALL.len() as isize;
+    let current = AppTab::ALL
+        .iter()
+        .position(|tab| tab == active_tab.get())
+        .unwrap_or_default() as isize;
+    let next = AppTab::ALL[(current + step).rem_euclid(count) as usize];
+    commands.run_system_cached_with(select_tab, next);
+}
+
 fn setup_footer<'a>(
     footer: &mut SceneHandle<'a, UiBuilder<'a, Entity>>,
     first_load_time: &mut Option<Duration>,
@@ -110,6 +213,22 @@ fn setup_footer<'a>(
     footer
         .get("refresh_button")
         .on_pressed(broadcast_fn(AppCommand::RebuildUi));
+    footer
+        .get("log_button")
+        .on_pressed(broadcast_fn(ToggleNotificationLog));
+    footer.get("selection").update_on(
+        broadcast::<UpdateSelectionStatus>(),
+        |id: TargetId, mut commands: Commands, selection: ActiveTab<&EntrySelection>| {
+            let Some(selection) = selection.get() else {
+                return;
+            };
+            let text = match selection.selected_count() {
+                0 => String::new(),
+                count => format!("{count} of {} selected", selection.rows().len()),
+            };
+            commands.ui_builder(*id).update_text(text);
+        },
+    );
 }
 
 fn update_tab_content_on_app_command(
@@ -147,6 +266,20 @@ fn update_tab_content_on_app_command(
                         settings_tab::init_settings_tab,
                     );
                 }
+                AppTab::Log => {
+                    commands.ui_builder(id).spawn_scene(
+                        ("tabs_log", "log_tab"),
+                        &mut scene_builder,
+                        log_tab::init_log_tab,
+                    );
+                }
+                AppTab::Bookmarks => {
+                    commands.ui_builder(id).spawn_scene(
+                        ("tabs_bookmarks", "bookmarks_tab"),
+                        &mut scene_builder,
+                        bookmarks_tab::init_bookmarks_tab,
+                    );
+                }
             }
 
             next_app_tab.set(*tab);
@@ -168,6 +301,35 @@ pub fn build_ui(
 
             root.edit("tab_buttons", setup_tab_buttons);
 
+            toasts::setup_toast_stack(&mut root.get("toasts"));
+            toasts::setup_notification_log(&mut root.get("notification_log"));
+
+            root.spawn_scene(("quick_open", "quick_open"), quick_open::setup_quick_open);
+            root.spawn_scene(
+                ("command_palette", "command_palette"),
+                command_palette::setup_command_palette,
+            );
+            root.spawn_scene(("open_with", "open_with"), open_with::setup_open_with);
+            root.spawn_scene(
+                ("context_menu", "context_menu"),
+                context_menu::setup_context_menu,
+            );
+            root.spawn_scene(("history", "history"), history::setup_history);
+            root.spawn_scene(
+                ("history", "history_dropdown"),
+                history::setup_history_dropdown,
+            );
+            root.spawn_scene(
+                ("places", "places_menu"),
+                main_tab::places::setup_places_menu,
+            );
+
+            #[cfg(debug_assertions)]
+            root.spawn_scene(
+                ("dev_overlay", "cobweb_warnings"),
+                cobweb_warnings::setup_cobweb_warnings,
+            );
+
             root.get("tab_content")
                 .update_on(broadcast::<AppCommand>(), update_tab_content_on_app_command)
                 .update_on(
@@ -185,13 +347,22 @@ pub fn build_ui(
 fn update_explorer_on_explorer_command(
     _: TargetId,
     broadcast_event: BroadcastEvent<ExplorerCommand>,
-    current_directory: Res<CurrentDirectory>,
-    mut location_history: ResMut<LocationHistory>,
+    mut tab: ActiveTab<(
+        &CurrentDirectory,
+        &LocationHistory,
+        &mut PreviewPath,
+        &mut PreviewLine,
+    )>,
     mut commands: Commands,
 ) {
     let Ok(event) = broadcast_event.try_read() else {
         return;
     };
+    let Some((current_directory, location_history, mut preview_path, mut preview_line)) =
+        tab.get_mut()
+    else {
+        return;
+    };
     info!("{event:?}");
 
     match event {
@@ -204,45 +375,217 @@ fn update_explorer_on_explorer_command(
                 commands.send_event(DirectoryChangeRequest::from(path));
             };
         }
-        ExplorerCommand::SetPreview(preview_path) => {
-            commands.insert_resource(PreviewPath::from(
-                preview_path
-                    .as_ref()
-                    .and_then(|path| path.canonicalize().ok()),
-            ));
+        ExplorerCommand::SetPreview(path) => {
+            **preview_line = None;
+            **preview_path = path.as_ref().and_then(|path| path.canonicalize().ok());
+        }
+        ExplorerCommand::PreviewLine(path, line) => {
+            **preview_line = Some(*line);
+            **preview_path = path.canonicalize().ok();
         }
         ExplorerCommand::HistoryBack => {
-            if let Some(prev) = location_history.back.pop() {
-                location_history.next.insert(0, current_directory.clone());
-                commands.send_event(DirectoryChangeRequest::from(prev));
-            }
+            jump_in_history(location_history, -1, &mut commands);
         }
         ExplorerCommand::HistoryNext => {
-            if let Some(next) = location_history.next.pop() {
-                location_history.back.push(current_directory.clone());
-                commands.send_event(DirectoryChangeRequest::from(next));
-            }
+            jump_in_history(location_history, 1, &mut commands);
+        }
+        ExplorerCommand::HistoryJump(steps) => {
+            jump_in_history(location_history, *steps, &mut commands);
         }
         ExplorerCommand::GotoParent => {
             if let Some(parent) = current_directory.parent().map(Path::to_owned) {
                 commands.send_event(DirectoryChangeRequest::from(parent));
             };
         }
+        ExplorerCommand::GotoHome => match xdg::home_dir() {
+            Some(home) => {
+                commands.send_event(DirectoryChangeRequest::from(home));
+            }
+            None => {
+                commands.send_event(Notification::warning("$HOME is not set"));
+            }
+        },
+        ExplorerCommand::GotoRoot => {
+            commands.send_event(DirectoryChangeRequest::from("/"));
+        }
+        ExplorerCommand::GotoUserDir(user_dir) => match user_dir.path() {
+            Some(path) if path.is_dir() => {
+                commands.send_event(DirectoryChangeRequest::from(path));
+            }
+            _ => {
+                commands.send_event(Notification::warning(format!(
+                    "There is no {} directory",
+                    user_dir.name()
+                )));
+            }
+        },
     }
 }
 
-fn clear_preview_path(mut preview_path: ResMut<PreviewPath>) {
-    _ = preview_path.take();
+fn jump_in_history(history: &LocationHistory, steps: isize, commands: &mut Commands) {
+    if let Some(target) = history.target(steps) {
+        commands.send_event(DirectoryChangeRequest::jump(target, steps));
+    }
+}
+
+fn clear_preview_path(mut preview_path: ActiveTab<&mut PreviewPath>) {
+    if let Some(mut preview_path) = preview_path.get_mut() {
+        _ = preview_path.take();
+    }
+}
+
+/// Makes every [ExplorerCommand] and [AppCommand] available in the command palette
+fn add_commands(app: &mut App) {
+    app.add_command(
+        RegisteredCommand::broadcast("Reload", ExplorerCommand::Reload)
+            .bind(KeyBinding::new(KeyCode::F5)),
+    )
+    .add_command(
+        RegisteredCommand::broadcast("Go back", ExplorerCommand::HistoryBack)
+            .bind(KeyBinding::new(KeyCode::ArrowLeft).alt()),
+    )
+    .add_command(
+        RegisteredCommand::broadcast("Go forward", ExplorerCommand::HistoryNext)
+            .bind(KeyBinding::new(KeyCode::ArrowRight).alt()),
+    )
+    .add_command(
+        RegisteredCommand::broadcast("Go to parent directory", ExplorerCommand::GotoParent)
+            .bind(KeyBinding::new(KeyCode::ArrowUp).alt()),
+    )
+    .add_command(
+        RegisteredCommand::broadcast("Go home", ExplorerCommand::GotoHome)
+            .bind(KeyBinding::new(KeyCode::Home).alt()),
+    )
+    .add_command(RegisteredCommand::broadcast(
+        "Go to root directory",
+        ExplorerCommand::GotoRoot,
+    ))
+    .add_command(RegisteredCommand::with_argument(
+        "Go to directory",
+        "a directory path",
+        |commands, path| {
+            commands
+                .react()
+                .broadcast(ExplorerCommand::SetDirectory(path.trim().into()));
+        },
+    ))
+    .add_command(RegisteredCommand::with_argument(
+        "Preview file",
+        "a file path, or nothing to close the preview",
+        |commands, path| {
+            let path = path.trim();
+            let path = (!path.is_empty()).then(|| PathBuf::from(path));
+            commands
+                .react()
+                .broadcast(ExplorerCommand::SetPreview(path));
+        },
+    ))
+    .add_command(RegisteredCommand::with_argument(
+        "Preview line",
+        "a file path and line number, like src/main.rs:12",
+        |commands, argument| {
+            let parsed = argument
+                .trim()
+                .rsplit_once(':')
+                .and_then(|(path, line)| Some((PathBuf::from(path), line.parse().ok()?)));
+            match parsed {
+                Some((path, line)) => {
+                    commands
+                        .react()
+                        .broadcast(ExplorerCommand::PreviewLine(path, line));
+                }
+                None => {
+                    commands.send_event(Notification::warning(format!(
+                        "expected path:line, got {argument:?}"
+                    )));
+                }
+            }
+        },
+    ))
+    .add_command(
+        RegisteredCommand::broadcast("Rebuild UI", AppCommand::RebuildUi)
+            .bind(KeyBinding::new(KeyCode::KeyR).ctrl().shift()),
+    )
+    .add_command(
+        RegisteredCommand::new("Show main tab", |commands| {
+            commands.run_system_cached_with(select_tab, AppTab::Main);
+        })
+        .bind(KeyBinding::new(KeyCode::Digit1).ctrl()),
+    )
+    .add_command(
+        RegisteredCommand::new("Show settings tab", |commands| {
+            commands.run_system_cached_with(select_tab, AppTab::Settings);
+        })
+        .bind(KeyBinding::new(KeyCode::Digit2).ctrl()),
+    )
+    .add_command(
+        RegisteredCommand::new("Show log tab", |commands| {
+            commands.run_system_cached_with(select_tab, AppTab::Log);
+        })
+        .bind(KeyBinding::new(KeyCode::Digit3).ctrl()),
+    )
+    .add_command(
+        RegisteredCommand::new("Show bookmarks tab", |commands| {
+            commands.run_system_cached_with(select_tab, AppTab::Bookmarks);
+        })
+        .bind(KeyBinding::new(KeyCode::Digit4).ctrl()),
+    )
+    .add_command(
+        RegisteredCommand::new("Next tab", |commands| {
+            commands.run_system_cached_with(cycle_tab, 1);
+        })
+        .bind(KeyBinding::new(KeyCode::Tab).ctrl()),
+    )
+    .add_command(
+        RegisteredCommand::new("Previous tab", |commands| {
+            commands.run_system_cached_with(cycle_tab, -1);
+        })
+        .bind(KeyBinding::new(KeyCode::Tab).ctrl().shift()),
+    )
+    .add_command(RegisteredCommand::broadcast(
+        "Toggle notification log",
+        ToggleNotificationLog,
+    ));
+    for user_dir in UserDir::ALL {
+        app.add_command(RegisteredCommand::broadcast(
+            format!("Go to {}", user_dir.name()),
+            ExplorerCommand::GotoUserDir(user_dir),
+        ));
+    }
 }
 
 pub fn ui_plugin(app: &mut App) {
+    #[cfg(debug_assertions)]
+    app.add_plugins(cobweb_warnings::cobweb_warnings_plugin);
     app.add_plugins(CobwebUiPlugin)
         .load("cobweb/manifest.cob")
-        .add_plugins((loading_screen_plugin, view_state_plugin))
+        .add_plugins((
+            loading_screen_plugin,
+            view_state_plugin,
+            toasts_plugin,
+            text_input_plugin,
+            (
+                main_tab_plugin,
+                entry_actions_plugin,
+                explorer_tabs_plugin,
+                places_plugin,
+                directory_tree_plugin,
+                columns_plugin,
+                dual_pane_plugin,
+                preview_plugin,
+            ),
+            command_palette_plugin,
+            context_menu_plugin,
+            history_plugin,
+            open_with_plugin,
+            quick_open_plugin,
+            settings_tab_plugin,
+            bookmarks_tab_plugin,
+            splitter_plugin,
+            log_tab_plugin,
+            add_commands,
+        ))
         .add_sub_state::<AppTab>()
-        .init_resource::<PanelLayout>()
-        .init_resource::<LocationHistory>()
-        .init_resource::<PreviewPath>()
         .register_component_type::<Marker>()
         .register_component_type::<NavigationButton>()
         .add_systems(
@@ -255,9 +598,19 @@ pub fn ui_plugin(app: &mut App) {
                     clear_preview_path,
                 )
                     .run_if(on_event::<CurrentDirectoryChanged>),
-                broadcast_fn(ui_events::UpdatePreview).run_if(resource_changed::<PreviewPath>),
-                broadcast_fn(ui_events::UpdateOverview)
-                    .run_if(resource_changed::<DirectoryEntries>),
+                // show the contents of the other tab
+                broadcast_fn(AppCommand::ChangeTab(AppTab::Main))
+                    .run_if(on_event::<ActiveTabChanged>),
+                broadcast_fn(ui_events::UpdatePreview).run_if(
+                    any_match_filter::<Changed<PreviewPath>>
+                        .or(any_match_filter::<Changed<PreviewLine>>),
+                ),
+                broadcast_fn(ui_events::UpdateOverview).run_if(
+                    any_match_filter::<Changed<DirectoryEntries>>.or(
+                        // results stream in, so batch them up
+                        on_timer(Duration::from_millis(250)).and(resource_changed::<SearchResults>),
+                    ),
+                ),
             ),
         )
         .add_systems(OnEnter(ViewState::Stable), build_ui)
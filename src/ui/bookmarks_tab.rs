@@ -0,0 +1,131 @@
+use crate::bookmarks::{Bookmarks, import_gtk_bookmarks};
+use crate::bridge::DirectoryChangeRequest;
+use crate::command_registry::{AddCommandExt, KeyBinding, RegisteredCommand};
+use crate::notifications::Notification;
+use crate::prelude::*;
+use crate::resources::{ActiveTab, CurrentDirectory};
+use crate::traits::ChangeTabExt;
+use crate::ui::text_input::{TextInputSubmitted, setup_text_input};
+use crate::ui::ui_events::UpdateBookmarks;
+use crate::ui::{AppTab, broadcast_fn};
+
+/// Goes to the bookmarked directory on the main tab
+pub(crate) fn open_bookmark(In(path): In<PathBuf>, mut commands: Commands) {
+    commands.send_event(DirectoryChangeRequest::from(path));
+    commands.change_tab(AppTab::Main);
+}
+
+fn add_bookmark(
+    mut bookmarks: ResMut<Bookmarks>,
+    directory: ActiveTab<&CurrentDirectory>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(directory) = directory.get() else {
+        return;
+    };
+    if !bookmarks.add(directory.to_path_buf(), None) {
+        notifications.write(Notification::info(format!(
+            "{directory} is already bookmarked"
+        )));
+    }
+}
+
+fn remove_bookmark(mut bookmarks: ResMut<Bookmarks>, directory: ActiveTab<&CurrentDirectory>) {
+    if let Some(directory) = directory.get() {
+        bookmarks.remove(directory);
+    }
+}
+
+/// Bookmarks the current directory, or removes its bookmark
+pub(crate) fn toggle_bookmark(
+    mut bookmarks: ResMut<Bookmarks>,
+    directory: ActiveTab<&CurrentDirectory>,
+) {
+    let Some(directory) = directory.get() else {
+        return;
+    };
+    if bookmarks.remove(directory).is_none() {
+        bookmarks.add(directory.to_path_buf(), None);
+    }
+}
+
+fn setup_toolbar<'a>(toolbar: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    toolbar.get("add_button").on_pressed(add_bookmark);
+    toolbar
+        .get("import_button")
+        .on_pressed(import_gtk_bookmarks);
+}
+
+pub fn init_bookmarks_tab<'a>(bookmarks_tab: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    bookmarks_tab.edit("toolbar", setup_toolbar);
+
+    bookmarks_tab.get("items::view::shim").update_on(
+        broadcast::<UpdateBookmarks>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         bookmarks: Res<Bookmarks>| {
+            commands.entity(*id).despawn_related::<Children>();
+            for bookmark in bookmarks.iter() {
+                let path = bookmark.path.clone();
+                let label = bookmark.label().into_owned();
+                commands.ui_builder(*id).spawn_scene(
+                    ("tabs_bookmarks", "bookmark_row"),
+                    &mut scene_builder,
+                    |row| {
+                        let mut name = row.get("name");
+                        setup_text_input(&mut name, &label);
+                        let renamed = path.clone();
+                        name.on_event::<TextInputSubmitted>().r(
+                            move |event: EntityEvent<TextInputSubmitted>,
+                                  mut bookmarks: ResMut<Bookmarks>| {
+                                let (_, TextInputSubmitted(value)) = event.read();
+                                bookmarks.rename(&renamed, value);
+                            },
+                        );
+                        row.get("path").update_text(path.to_string_lossy());
+                        let opened = path.clone();
+                        row.get("open_button")
+                            .on_pressed(move |mut commands: Commands| {
+                                commands.run_system_cached_with(open_bookmark, opened.clone());
+                            });
+                        for (button, step) in [("up_button", -1), ("down_button", 1)] {
+                            let moved = path.clone();
+                            row.get(button)
+                                .on_pressed(move |mut bookmarks: ResMut<Bookmarks>| {
+                                    bookmarks.move_bookmark(&moved, step);
+                                });
+                        }
+                        let removed = path.clone();
+                        row.get("remove_button").on_pressed(
+                            move |mut bookmarks: ResMut<Bookmarks>| {
+                                bookmarks.remove(&removed);
+                            },
+                        );
+                    },
+                );
+            }
+        },
+    );
+}
+
+pub(crate) fn bookmarks_tab_plugin(app: &mut App) {
+    app.add_command(
+        RegisteredCommand::new("Add bookmark", |commands| {
+            commands.run_system_cached(add_bookmark);
+        })
+        .bind(KeyBinding::new(KeyCode::KeyD).ctrl()),
+    )
+    .add_command(RegisteredCommand::new("Remove bookmark", |commands| {
+        commands.run_system_cached(remove_bookmark);
+    }))
+    .add_command(RegisteredCommand::new("Import GTK bookmarks", |commands| {
+        commands.run_system_cached(import_gtk_bookmarks);
+    }))
+    .add_systems(
+        Update,
+        broadcast_fn(UpdateBookmarks).run_if(
+            resource_changed::<Bookmarks>.or(any_match_filter::<Changed<CurrentDirectory>>),
+        ),
+    );
+}
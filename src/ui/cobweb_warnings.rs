@@ -0,0 +1,67 @@
+use crate::cobweb_warning_subscriber::CobwebWarnings;
+use crate::prelude::*;
+use crate::ui::broadcast_fn;
+use crate::ui::ui_events::UpdateCobwebWarnings;
+
+/// Developer overlay listing cobweb's extraction warnings as they come in
+pub fn setup_cobweb_warnings<'a>(overlay: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    overlay.update_on(
+        broadcast::<UpdateCobwebWarnings>(),
+        |id: TargetId,
+         warnings: Res<CobwebWarnings>,
+         mut display_controls: Query<&mut DisplayControl>| {
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = if warnings.is_empty() {
+                    DisplayControl::Hide
+                } else {
+                    DisplayControl::Show
+                };
+            }
+        },
+    );
+
+    overlay
+        .get("header::clear_button")
+        .on_pressed(|mut warnings: ResMut<CobwebWarnings>| warnings.clear());
+
+    overlay.get("header::title").update_on(
+        broadcast::<UpdateCobwebWarnings>(),
+        |id: TargetId, mut commands: Commands, warnings: Res<CobwebWarnings>| {
+            commands
+                .ui_builder(*id)
+                .update_text(format!("cobweb warnings ({})", warnings.len()));
+        },
+    );
+
+    overlay.get("entries").update_on(
+        broadcast::<UpdateCobwebWarnings>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         warnings: Res<CobwebWarnings>| {
+            commands.entity(*id).despawn_related::<Children>();
+            for warning in warnings.iter() {
+                commands.ui_builder(*id).spawn_scene(
+                    ("dev_overlay", "warning_entry"),
+                    &mut scene_builder,
+                    |entry| {
+                        entry
+                            .get("location::file")
+                            .update_text(warning.file.as_deref().unwrap_or("<unknown file>"));
+                        entry
+                            .get("location::scene")
+                            .update_text(warning.scene.as_deref().unwrap_or_default());
+                        entry.get("message").update_text(warning.message.clone());
+                    },
+                );
+            }
+        },
+    );
+}
+
+pub(crate) fn cobweb_warnings_plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        broadcast_fn(UpdateCobwebWarnings).run_if(resource_changed::<CobwebWarnings>),
+    );
+}
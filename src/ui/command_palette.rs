@@ -0,0 +1,322 @@
+use std::cmp::Reverse;
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::InputSystem;
+
+use crate::command_registry::{AddCommandExt, CommandRegistry, KeyBinding, RegisteredCommand};
+use crate::prelude::*;
+use crate::search::file_index::fuzzy_score;
+use crate::ui::broadcast_fn;
+use crate::ui::context_menu::ContextMenuOpen;
+use crate::ui::history::{History, HistoryDropdown};
+use crate::ui::main_tab::places::PlacesMenu;
+use crate::ui::open_with::OpenWith;
+use crate::ui::text_input::{FocusedTextInput, TextInput, TextInputCancelled, TextInputChanged,
+                            TextInputSubmitted, setup_text_input};
+use crate::ui::ui_events::UpdateCommandPalette;
+
+#[derive(Debug, Default, Resource)]
+pub(crate) struct CommandPalette {
+    open: bool,
+    query: String,
+    /// index into [PaletteMatches]
+    selected: usize,
+    /// index into the [CommandRegistry] of the command waiting for its argument
+    prompt: Option<usize>,
+}
+
+/// The popups that close on Escape by themselves
+#[derive(SystemParam)]
+struct Popups<'w> {
+    context_menu: Res<'w, ContextMenuOpen>,
+    places: Res<'w, PlacesMenu>,
+    history: Res<'w, History>,
+    history_dropdown: Res<'w, HistoryDropdown>,
+    open_with: Res<'w, OpenWith>,
+}
+
+impl Popups<'_> {
+    fn any_open(&self) -> bool {
+        self.context_menu.is_open()
+            || self.places.is_open()
+            || self.history.is_open()
+            || self.history_dropdown.is_open()
+            || self.open_with.is_open()
+    }
+}
+
+/// Indices into the [CommandRegistry], best match first
+#[derive(Debug, Default, Deref, DerefMut, Resource)]
+struct PaletteMatches(Vec<usize>);
+
+#[derive(Component, Debug)]
+pub(crate) struct CommandPaletteInput;
+
+fn close(palette: &mut CommandPalette, focused: &mut FocusedTextInput) {
+    palette.open = false;
+    **focused = None;
+}
+
+fn submit(
+    event: EntityEvent<TextInputSubmitted>,
+    mut commands: Commands,
+    mut palette: ResMut<CommandPalette>,
+    mut focused: ResMut<FocusedTextInput>,
+    mut inputs: Query<&mut TextInput>,
+    matches: Res<PaletteMatches>,
+    registry: Res<CommandRegistry>,
+) {
+    let (input, TextInputSubmitted(value)) = event.read();
+    if let Some(i) = palette.prompt {
+        registry[i].run(&mut commands, value);
+        close(&mut palette, &mut focused);
+        return;
+    }
+    let Some(&i) = matches.get(palette.selected) else {
+        return;
+    };
+    if registry[i].argument.is_some() {
+        // keep the palette open and ask for the argument
+        if let Ok(mut text_input) = inputs.get_mut(input) {
+            text_input.value.clear();
+        }
+        palette.prompt = Some(i);
+        palette.query.clear();
+    } else {
+        registry[i].run(&mut commands, "");
+        close(&mut palette, &mut focused);
+    }
+}
+
+pub fn setup_command_palette<'a>(popup: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    popup.update_on(
+        broadcast::<UpdateCommandPalette>(),
+        |id: TargetId,
+         palette: Res<CommandPalette>,
+         mut display_controls: Query<&mut DisplayControl>| {
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = if palette.open {
+                    DisplayControl::Show
+                } else {
+                    DisplayControl::Hide
+                };
+            }
+        },
+    );
+
+    let mut input = popup.get("input");
+    setup_text_input(&mut input, "run a command");
+    input.insert(CommandPaletteInput);
+    input.on_event::<TextInputChanged>().r(
+        |event: EntityEvent<TextInputChanged>, mut palette: ResMut<CommandPalette>| {
+            let (_, TextInputChanged(query)) = event.read();
+            if palette.prompt.is_none() {
+                palette.query = query.clone();
+                palette.selected = 0;
+            }
+        },
+    );
+    input.on_event::<TextInputSubmitted>().r(submit);
+    input.on_event::<TextInputCancelled>().r(
+        |mut palette: ResMut<CommandPalette>, mut focused: ResMut<FocusedTextInput>| {
+            close(&mut palette, &mut focused);
+        },
+    );
+
+    popup.get("commands").update_on(
+        broadcast::<UpdateCommandPalette>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         palette: Res<CommandPalette>,
+         matches: Res<PaletteMatches>,
+         registry: Res<CommandRegistry>| {
+            commands.entity(*id).despawn_related::<Children>();
+            if palette.prompt.is_some() {
+                return;
+            }
+            for (i, command) in matches.iter().map(|i| &registry[*i]).enumerate() {
+                commands.ui_builder(*id).spawn_scene(
+                    ("command_palette", "command_item"),
+                    &mut scene_builder,
+                    |item| {
+                        if i == palette.selected {
+                            item.insert(BackgroundColor(Color::srgb(0.15, 0.39, 0.92)));
+                        }
+                        let name = match command.argument {
+                            Some(_) => format!("{}...", command.name),
+                            None => command.name.clone(),
+                        };
+                        item.get("name").update_text(name);
+                        if let Some(key_binding) = command.key_binding {
+                            item.get("key_binding").update_text(key_binding.to_string());
+                        }
+                    },
+                );
+            }
+        },
+    );
+
+    popup.get("status").update_on(
+        broadcast::<UpdateCommandPalette>(),
+        |id: TargetId,
+         mut commands: Commands,
+         palette: Res<CommandPalette>,
+         matches: Res<PaletteMatches>,
+         registry: Res<CommandRegistry>| {
+            let status = match palette.prompt.map(|i| &registry[i]) {
+                Some(RegisteredCommand {
+                    name,
+                    argument: Some(prompt),
+                    ..
+                }) => format!("{name}: enter {prompt}"),
+                _ => format!("{} of {} commands", matches.len(), registry.len()),
+            };
+            commands.ui_builder(*id).update_text(status);
+        },
+    );
+}
+
+fn toggle_command_palette(
+    mut palette: ResMut<CommandPalette>,
+    mut focused: ResMut<FocusedTextInput>,
+    mut inputs: Query<(Entity, &mut TextInput), With<CommandPaletteInput>>,
+) {
+    if palette.open {
+        close(&mut palette, &mut focused);
+        return;
+    }
+    let Ok((input, mut text_input)) = inputs.single_mut() else {
+        return;
+    };
+    text_input.value.clear();
+    *palette = CommandPalette {
+        open: true,
+        ..default()
+    };
+    **focused = Some(input);
+}
+
+/// Runs the command called `name`, asking for its argument in the palette first if it has one
+pub(crate) fn run_command_by_name(
+    In(name): In<String>,
+    mut commands: Commands,
+    registry: Res<CommandRegistry>,
+    mut palette: ResMut<CommandPalette>,
+    mut focused: ResMut<FocusedTextInput>,
+    mut inputs: Query<(Entity, &mut TextInput), With<CommandPaletteInput>>,
+) {
+    let Some(i) = registry.iter().position(|command| command.name == name) else {
+        warn!("there is no command {name:?}");
+        return;
+    };
+    if registry[i].argument.is_none() {
+        registry[i].run(&mut commands, "");
+        return;
+    }
+    let Ok((input, mut text_input)) = inputs.single_mut() else {
+        return;
+    };
+    text_input.value.clear();
+    *palette = CommandPalette {
+        open: true,
+        prompt: Some(i),
+        ..default()
+    };
+    **focused = Some(input);
+}
+
+/// Runs commands by their key binding, before the text inputs and popups handle the same keys
+fn run_bound_commands(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    focused: Res<FocusedTextInput>,
+    popups: Popups,
+    registry: Res<CommandRegistry>,
+) {
+    // plain keys belong to the text input being edited or to the open popup, Escape closes them
+    let plain_keys_taken = focused.is_some() || popups.any_open();
+    for command in registry.iter() {
+        let Some(key_binding) = command.key_binding else {
+            continue;
+        };
+        if plain_keys_taken && !key_binding.is_modified() {
+            continue;
+        }
+        if key_binding.just_pressed(&keys) {
+            if command.argument.is_some() {
+                // ask for the argument first
+                commands.run_system_cached_with(run_command_by_name, command.name.clone());
+            } else {
+                command.run(&mut commands, "");
+            }
+        }
+    }
+}
+
+fn move_selection(
+    keys: Res<ButtonInput<KeyCode>>,
+    matches: Res<PaletteMatches>,
+    mut palette: ResMut<CommandPalette>,
+) {
+    if matches.is_empty() || palette.prompt.is_some() {
+        return;
+    }
+    let last = matches.len() - 1;
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        palette.selected = if palette.selected >= last {
+            0
+        } else {
+            palette.selected + 1
+        };
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        palette.selected = palette.selected.checked_sub(1).unwrap_or(last);
+    }
+}
+
+fn rank_matches(
+    palette: Res<CommandPalette>,
+    registry: Res<CommandRegistry>,
+    mut matches: ResMut<PaletteMatches>,
+) {
+    if palette.query.trim().is_empty() {
+        // keep the registration order
+        **matches = (0..registry.len()).collect();
+        return;
+    }
+    **matches = registry
+        .iter()
+        .enumerate()
+        .filter_map(|(i, command)| Some((fuzzy_score(&palette.query, &command.name)?, i)))
+        .sorted_by_key(|(score, i)| (Reverse(*score), *i))
+        .map(|(_, i)| i)
+        .collect_vec();
+}
+
+pub(crate) fn command_palette_plugin(app: &mut App) {
+    app.init_resource::<CommandRegistry>()
+        .init_resource::<CommandPalette>()
+        .init_resource::<PaletteMatches>()
+        .add_command(
+            RegisteredCommand::new("Command palette", |commands| {
+                commands.run_system_cached(toggle_command_palette);
+            })
+            .bind(KeyBinding::new(KeyCode::KeyP).ctrl().shift()),
+        )
+        .add_systems(PreUpdate, run_bound_commands.after(InputSystem))
+        .add_systems(
+            Update,
+            (
+                move_selection.run_if(|palette: Res<CommandPalette>| palette.open),
+                rank_matches.run_if(
+                    (|palette: Res<CommandPalette>| palette.open)
+                        .and(resource_changed::<CommandPalette>),
+                ),
+                broadcast_fn(UpdateCommandPalette).run_if(
+                    resource_changed::<CommandPalette>.or(resource_changed::<PaletteMatches>),
+                ),
+            )
+                .chain(),
+        );
+}
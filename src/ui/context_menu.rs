@@ -0,0 +1,135 @@
+use crate::fs::EntryType;
+use crate::prelude::*;
+use crate::ui::command_palette::run_command_by_name;
+
+/// Names the command a menu item runs, see `context_menu.cob`
+#[derive(Clone, Component, Debug, Default, PartialEq, Reflect)]
+struct MenuCommand(String);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContextMenuKind {
+    Entry(EntryType),
+    /// the empty space around the entries
+    Background,
+}
+
+impl ContextMenuKind {
+    fn scene(&self) -> &'static str {
+        match self {
+            Self::Entry(EntryType::File) => "file",
+            Self::Entry(EntryType::Directory) => "directory",
+            Self::Entry(EntryType::Symlink) => "symlink",
+            Self::Entry(EntryType::Unknown) => "unknown",
+            Self::Background => "background",
+        }
+    }
+}
+
+/// Broadcast to show a context menu, `position` is where the window was clicked
+#[derive(Clone, Copy, Debug)]
+pub struct OpenContextMenu {
+    pub kind: ContextMenuKind,
+    pub position: Vec2,
+}
+
+/// Broadcast to hide the context menu
+#[derive(Clone, Copy, Debug, Default)]
+struct CloseContextMenu;
+
+#[derive(Debug, Default, Resource)]
+pub(crate) struct ContextMenuOpen(bool);
+
+impl ContextMenuOpen {
+    pub(crate) fn is_open(&self) -> bool {
+        self.0
+    }
+}
+
+pub fn setup_context_menu<'a>(layer: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    layer
+        .update_on(
+            broadcast::<OpenContextMenu>(),
+            |id: TargetId,
+             mut open: ResMut<ContextMenuOpen>,
+             mut display_controls: Query<&mut DisplayControl>| {
+                open.0 = true;
+                if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                    *display_control = DisplayControl::Show;
+                }
+            },
+        )
+        .update_on(
+            broadcast::<CloseContextMenu>(),
+            |id: TargetId,
+             mut open: ResMut<ContextMenuOpen>,
+             mut display_controls: Query<&mut DisplayControl>| {
+                open.0 = false;
+                if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                    *display_control = DisplayControl::Hide;
+                }
+            },
+        )
+        // clicks on menu items bubble up to here, clicks anywhere else only close the menu
+        .observe(
+            |trigger: Trigger<Pointer<Click>>,
+             mut commands: Commands,
+             menu_commands: Query<&MenuCommand>,
+             parents: Query<&ChildOf>| {
+                let clicked = trigger.event().target;
+                if trigger.event().button == PointerButton::Primary
+                    && let Some(MenuCommand(name)) = std::iter::once(clicked)
+                        .chain(parents.iter_ancestors(clicked))
+                        .find_map(|e| menu_commands.get(e).ok())
+                {
+                    commands.run_system_cached_with(run_command_by_name, name.clone());
+                }
+                commands.react().broadcast(CloseContextMenu);
+            },
+        );
+
+    layer
+        .get("menu")
+        .update_on(
+            broadcast::<OpenContextMenu>(),
+            |id: TargetId,
+             event: BroadcastEvent<OpenContextMenu>,
+             mut commands: Commands,
+             mut scene_builder: SceneBuilder,
+             mut nodes: Query<&mut Node>| {
+                let Ok(OpenContextMenu { kind, position }) = event.try_read() else {
+                    return;
+                };
+                commands.entity(*id).despawn_related::<Children>();
+                commands.ui_builder(*id).spawn_scene(
+                    ("context_menu", kind.scene()),
+                    &mut scene_builder,
+                    |_| {},
+                );
+                if let Ok(mut node) = nodes.get_mut(*id) {
+                    node.left = Val::Px(position.x);
+                    node.top = Val::Px(position.y);
+                }
+            },
+        )
+        .update_on(
+            broadcast::<CloseContextMenu>(),
+            |id: TargetId, mut commands: Commands| {
+                commands.entity(*id).despawn_related::<Children>();
+            },
+        );
+}
+
+fn close_on_escape(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        commands.react().broadcast(CloseContextMenu);
+    }
+}
+
+pub(crate) fn context_menu_plugin(app: &mut App) {
+    app.init_resource::<ContextMenuOpen>()
+        .register_component_type::<MenuCommand>()
+        .add_systems(
+            Update,
+            close_on_escape.run_if(|open: Res<ContextMenuOpen>| open.0),
+        );
+}
@@ -0,0 +1,350 @@
+use chrono::Local;
+
+use crate::command_registry::{AddCommandExt, KeyBinding, RegisteredCommand};
+use crate::navigation::LocationHistory;
+use crate::prelude::*;
+use crate::recent_locations::RecentLocations;
+use crate::resources::ActiveTab;
+use crate::search::file_index::fuzzy_score;
+use crate::ui::text_input::{FocusedTextInput, TextInput, TextInputCancelled, TextInputChanged,
+                            TextInputSubmitted, setup_text_input};
+use crate::ui::ui_events::{UpdateHistory, UpdateHistoryDropdown};
+use crate::ui::{ExplorerCommand, broadcast_fn};
+
+/// the best ranked recent locations
+const MAX_SHOWN_RECENT: usize = 10;
+
+/// the newest visits
+const MAX_SHOWN_VISITS: usize = 50;
+
+/// Which side of the [LocationHistory] a dropdown lists
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HistoryDirection {
+    Back,
+    Next,
+}
+
+/// The entry of a dropdown, see [ExplorerCommand::HistoryJump]
+#[derive(Clone, Component, Copy, Debug)]
+struct HistoryStep(isize);
+
+/// The dropdown of the back or next button, `position` is where the window was clicked
+#[derive(Debug, Default, Resource)]
+pub struct HistoryDropdown(Option<(HistoryDirection, Vec2)>);
+
+impl HistoryDropdown {
+    pub fn is_open(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+/// The panel of visited directories
+#[derive(Debug, Default, Resource)]
+pub(crate) struct History {
+    open: bool,
+    query: String,
+}
+
+impl History {
+    pub(crate) fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+#[derive(Component, Debug)]
+struct HistoryInput;
+
+fn matches(query: &str, path: &Path) -> bool {
+    fuzzy_score(query, &path.to_string_lossy()).is_some()
+}
+
+/// The ranked recent locations that match the query
+fn shown_recent(history: &History, recent: &RecentLocations) -> Vec<PathBuf> {
+    recent
+        .ranked(Local::now().timestamp())
+        .into_iter()
+        .filter(|path| matches(&history.query, path))
+        .take(MAX_SHOWN_RECENT)
+        .map(Path::to_owned)
+        .collect()
+}
+
+fn close(history: &mut History, focused: &mut FocusedTextInput) {
+    history.open = false;
+    **focused = None;
+}
+
+fn go_to(path: PathBuf) -> impl Fn(Commands, ResMut<History>, ResMut<FocusedTextInput>) {
+    move |mut commands, mut history, mut focused| {
+        commands
+            .react()
+            .broadcast(ExplorerCommand::SetDirectory(path.clone()));
+        close(&mut history, &mut focused);
+    }
+}
+
+/// Goes to the best recent location, or the newest visit when none match
+fn go_to_first_match(
+    mut commands: Commands,
+    mut history: ResMut<History>,
+    mut focused: ResMut<FocusedTextInput>,
+    recent: Res<RecentLocations>,
+) {
+    let first = shown_recent(&history, &recent)
+        .into_iter()
+        .next()
+        .or_else(|| {
+            recent
+                .visits()
+                .find(|visit| matches(&history.query, &visit.path))
+                .map(|visit| visit.path.clone())
+        });
+    if let Some(path) = first {
+        commands
+            .react()
+            .broadcast(ExplorerCommand::SetDirectory(path));
+    }
+    close(&mut history, &mut focused);
+}
+
+pub fn setup_history_dropdown<'a>(layer: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    layer
+        .update_on(
+            broadcast::<UpdateHistoryDropdown>(),
+            |id: TargetId,
+             dropdown: Res<HistoryDropdown>,
+             mut display_controls: Query<&mut DisplayControl>| {
+                if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                    *display_control = match dropdown.0 {
+                        Some(_) => DisplayControl::Show,
+                        None => DisplayControl::Hide,
+                    };
+                }
+            },
+        )
+        // clicks on entries bubble up to here, clicks anywhere else only close the dropdown
+        .observe(
+            |trigger: Trigger<Pointer<Click>>,
+             mut commands: Commands,
+             mut dropdown: ResMut<HistoryDropdown>,
+             steps: Query<&HistoryStep>,
+             parents: Query<&ChildOf>| {
+                let clicked = trigger.event().target;
+                if trigger.event().button == PointerButton::Primary
+                    && let Some(HistoryStep(steps)) = std::iter::once(clicked)
+                        .chain(parents.iter_ancestors(clicked))
+                        .find_map(|e| steps.get(e).ok())
+                {
+                    commands
+                        .react()
+                        .broadcast(ExplorerCommand::HistoryJump(*steps));
+                }
+                dropdown.0 = None;
+            },
+        );
+
+    layer.get("menu").update_on(
+        broadcast::<UpdateHistoryDropdown>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         dropdown: Res<HistoryDropdown>,
+         history: ActiveTab<&LocationHistory>,
+         mut nodes: Query<&mut Node>| {
+            commands.entity(*id).despawn_related::<Children>();
+            let (Some((direction, position)), Some(history)) = (dropdown.0, history.get()) else {
+                return;
+            };
+            let (entries, sign) = match direction {
+                HistoryDirection::Back => (history.back(), -1),
+                HistoryDirection::Next => (history.next(), 1),
+            };
+            // nearest first
+            for (i, path) in entries.iter().rev().enumerate() {
+                commands.ui_builder(*id).spawn_scene(
+                    ("history", "history_dropdown_item"),
+                    &mut scene_builder,
+                    |item| {
+                        item.insert(HistoryStep(sign * (i as isize + 1)));
+                        item.get("text").update_text(path.to_string_lossy());
+                    },
+                );
+            }
+            if let Ok(mut node) = nodes.get_mut(*id) {
+                node.left = Val::Px(position.x);
+                node.top = Val::Px(position.y);
+            }
+        },
+    );
+}
+
+pub fn setup_history<'a>(popup: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    popup.update_on(
+        broadcast::<UpdateHistory>(),
+        |id: TargetId, history: Res<History>, mut display_controls: Query<&mut DisplayControl>| {
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = if history.open {
+                    DisplayControl::Show
+                } else {
+                    DisplayControl::Hide
+                };
+            }
+        },
+    );
+
+    let mut input = popup.get("input");
+    setup_text_input(&mut input, "search visited directories");
+    input.insert(HistoryInput);
+    input.on_event::<TextInputChanged>().r(
+        |event: EntityEvent<TextInputChanged>, mut history: ResMut<History>| {
+            let (_, TextInputChanged(query)) = event.read();
+            history.query = query.clone();
+        },
+    );
+    input.on_event::<TextInputSubmitted>().r(go_to_first_match);
+    input.on_event::<TextInputCancelled>().r(
+        |mut history: ResMut<History>, mut focused: ResMut<FocusedTextInput>| {
+            close(&mut history, &mut focused);
+        },
+    );
+
+    popup.get("recent").update_on(
+        broadcast::<UpdateHistory>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         history: Res<History>,
+         recent: Res<RecentLocations>| {
+            commands.entity(*id).despawn_related::<Children>();
+            if !history.open {
+                return;
+            }
+            for path in shown_recent(&history, &recent) {
+                commands.ui_builder(*id).spawn_scene(
+                    ("history", "history_recent_item"),
+                    &mut scene_builder,
+                    |item| {
+                        let label = path
+                            .file_name()
+                            .map_or(path.to_string_lossy(), |name| name.to_string_lossy())
+                            .into_owned();
+                        item.get("text").update_text(label);
+                        item.on_pressed(go_to(path));
+                    },
+                );
+            }
+        },
+    );
+
+    popup.get("visits").update_on(
+        broadcast::<UpdateHistory>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         history: Res<History>,
+         recent: Res<RecentLocations>| {
+            commands.entity(*id).despawn_related::<Children>();
+            if !history.open {
+                return;
+            }
+            for visit in recent
+                .visits()
+                .filter(|visit| matches(&history.query, &visit.path))
+                .take(MAX_SHOWN_VISITS)
+            {
+                let time = visit
+                    .local_time()
+                    .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                let path = visit.path.clone();
+                commands.ui_builder(*id).spawn_scene(
+                    ("history", "history_visit_item"),
+                    &mut scene_builder,
+                    |item| {
+                        item.get("time").update_text(time);
+                        let mut open = item.get("open");
+                        open.get("text").update_text(path.to_string_lossy());
+                        open.on_pressed(go_to(path));
+                    },
+                );
+            }
+        },
+    );
+
+    popup.get("status").update_on(
+        broadcast::<UpdateHistory>(),
+        |id: TargetId, mut commands: Commands, recent: Res<RecentLocations>| {
+            let count = recent.visits().count();
+            commands
+                .ui_builder(*id)
+                .update_text(format!("{count} visits"));
+        },
+    );
+}
+
+/// Right clicks on the back and next buttons list where they go
+pub fn open_history_dropdown(
+    In((direction, position)): In<(HistoryDirection, Vec2)>,
+    mut dropdown: ResMut<HistoryDropdown>,
+) {
+    dropdown.0 = Some((direction, position));
+}
+
+fn toggle_history(
+    mut history: ResMut<History>,
+    mut focused: ResMut<FocusedTextInput>,
+    mut inputs: Query<(Entity, &mut TextInput), With<HistoryInput>>,
+) {
+    if history.open {
+        close(&mut history, &mut focused);
+        return;
+    }
+    let Ok((input, mut text_input)) = inputs.single_mut() else {
+        return;
+    };
+    text_input.value.clear();
+    *history = History {
+        open: true,
+        ..default()
+    };
+    **focused = Some(input);
+}
+
+fn close_on_escape(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut history: ResMut<History>,
+    mut dropdown: ResMut<HistoryDropdown>,
+    mut focused: ResMut<FocusedTextInput>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    if history.open {
+        close(&mut history, &mut focused);
+    }
+    dropdown.0 = None;
+}
+
+pub(crate) fn history_plugin(app: &mut App) {
+    app.init_resource::<History>()
+        .init_resource::<HistoryDropdown>()
+        .add_command(
+            RegisteredCommand::new("Show history", |commands| {
+                commands.run_system_cached(toggle_history);
+            })
+            .bind(KeyBinding::new(KeyCode::KeyH).ctrl()),
+        )
+        .add_systems(
+            Update,
+            (
+                close_on_escape.run_if(
+                    (|history: Res<History>| history.open)
+                        .or(|dropdown: Res<HistoryDropdown>| dropdown.0.is_some()),
+                ),
+                broadcast_fn(UpdateHistory)
+                    .run_if(resource_changed::<History>.or(resource_changed::<RecentLocations>)),
+                broadcast_fn(UpdateHistoryDropdown).run_if(resource_changed::<HistoryDropdown>),
+            )
+                .chain(),
+        );
+}
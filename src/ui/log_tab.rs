@@ -0,0 +1,230 @@
+use std::{fs, io};
+
+use bevy::log::Level;
+use bevy::log::tracing::Level as TracingLevel;
+use bevy::time::common_conditions::on_timer;
+use chrono::Local;
+
+use crate::clipboard::CopyToClipboard;
+use crate::log_capture::{LogBuffer, LogRecord};
+use crate::notifications::Notification;
+use crate::prelude::*;
+use crate::ui::text_input::{TextInputChanged, setup_text_input};
+use crate::ui::ui_events::UpdateLogView;
+use crate::xdg;
+
+/// rebuilding hundreds of rows is not free, so only the most recent matches are shown
+const MAX_SHOWN_RECORDS: usize = 300;
+
+const LEVELS: [(&str, Level); 5] = [
+    ("error", Level::ERROR),
+    ("warn", Level::WARN),
+    ("info", Level::INFO),
+    ("debug", Level::DEBUG),
+    ("trace", Level::TRACE),
+];
+
+#[derive(Debug, Resource)]
+pub struct LogFilter {
+    levels: Vec<Level>,
+    target: String,
+    search: String,
+    /// while paused, records newer than this id are not shown
+    paused_at: Option<u64>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            levels: LEVELS.iter().map(|(_, level)| *level).collect(),
+            target: default(),
+            search: default(),
+            paused_at: None,
+        }
+    }
+}
+
+impl LogFilter {
+    fn matches(&self, record: &LogRecord) -> bool {
+        self.levels.contains(&record.level)
+            && self.paused_at.is_none_or(|id| record.id <= id)
+            && record.target.contains(&self.target)
+            && (self.search.is_empty()
+                || record
+                    .message
+                    .to_lowercase()
+                    .contains(&self.search.to_lowercase()))
+    }
+    fn set_level(&mut self, level: Level, enabled: bool) {
+        self.levels.retain(|l| *l != level);
+        if enabled {
+            self.levels.push(level);
+        }
+    }
+}
+
+fn level_color(level: &TracingLevel) -> Color {
+    match *level {
+        Level::ERROR => Color::srgb(0.86, 0.15, 0.15),
+        Level::WARN => Color::srgb(0.85, 0.47, 0.02),
+        Level::INFO => Color::srgb(0.09, 0.64, 0.29),
+        Level::DEBUG => Color::srgb(0.15, 0.39, 0.92),
+        _ => Color::srgb(0.45, 0.45, 0.45),
+    }
+}
+
+fn filtered_lines(buffer: &LogBuffer, filter: &LogFilter) -> String {
+    buffer
+        .iter()
+        .filter(|record| filter.matches(record))
+        .map(LogRecord::to_line)
+        .join("\n")
+}
+
+fn export_log(text: &str) -> io::Result<PathBuf> {
+    let dir = xdg::state_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory"))?
+        .join("logs");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "corvus-{}.log",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+fn setup_toolbar<'a>(toolbar: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    for (name, level) in LEVELS {
+        toolbar
+            .get(format!("levels::{name}"))
+            .on_check(move |mut filter: ResMut<LogFilter>| filter.set_level(level, true))
+            .on_uncheck(move |mut filter: ResMut<LogFilter>| filter.set_level(level, false))
+            .update(
+                move |id: TargetId, mut commands: Commands, filter: Res<LogFilter>| {
+                    if filter.levels.contains(&level) {
+                        commands.react().entity_event(*id, Check);
+                    }
+                },
+            );
+    }
+
+    let mut target = toolbar.get("target");
+    setup_text_input(&mut target, "target");
+    target.on_event::<TextInputChanged>().r(
+        |event: EntityEvent<TextInputChanged>, mut filter: ResMut<LogFilter>| {
+            let (_, TextInputChanged(value)) = event.read();
+            filter.target = value.clone();
+        },
+    );
+
+    let mut search = toolbar.get("search");
+    setup_text_input(&mut search, "search");
+    search.on_event::<TextInputChanged>().r(
+        |event: EntityEvent<TextInputChanged>, mut filter: ResMut<LogFilter>| {
+            let (_, TextInputChanged(value)) = event.read();
+            filter.search = value.clone();
+        },
+    );
+
+    toolbar
+        .get("pause_button")
+        .on_check(|buffer: Res<LogBuffer>, mut filter: ResMut<LogFilter>| {
+            filter.paused_at = Some(buffer.last_id().unwrap_or_default());
+        })
+        .on_uncheck(|mut filter: ResMut<LogFilter>| {
+            filter.paused_at = None;
+        });
+
+    toolbar.get("copy_button").on_pressed(
+        |buffer: Res<LogBuffer>,
+         filter: Res<LogFilter>,
+         mut writer: EventWriter<CopyToClipboard>| {
+            writer.write(CopyToClipboard(filtered_lines(&buffer, &filter)));
+        },
+    );
+
+    toolbar.get("export_button").on_pressed(
+        |buffer: Res<LogBuffer>,
+         filter: Res<LogFilter>,
+         mut notifications: EventWriter<Notification>| {
+            let notification = match export_log(&filtered_lines(&buffer, &filter)) {
+                Ok(path) => Notification::info(format!("Log exported to {}", path.display())),
+                Err(error) => Notification::warning(format!("Cannot export log: {error}")),
+            };
+            notifications.write(notification);
+        },
+    );
+
+    toolbar
+        .get("clear_button")
+        .on_pressed(|mut buffer: ResMut<LogBuffer>| buffer.clear());
+}
+
+pub fn init_log_tab<'a>(log_tab: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    log_tab.edit("toolbar", setup_toolbar);
+
+    log_tab.get("status").update_on(
+        broadcast::<UpdateLogView>(),
+        |id: TargetId, mut commands: Commands, buffer: Res<LogBuffer>, filter: Res<LogFilter>| {
+            let matching = buffer.iter().filter(|r| filter.matches(r)).count();
+            let paused = if filter.paused_at.is_some() {
+                " (paused)"
+            } else {
+                ""
+            };
+            commands.ui_builder(*id).update_text(format!(
+                "showing {} of {matching} matching records, {} buffered{paused}",
+                matching.min(MAX_SHOWN_RECORDS),
+                buffer.len()
+            ));
+        },
+    );
+
+    log_tab.get("records::view::shim").update_on(
+        broadcast::<UpdateLogView>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         buffer: Res<LogBuffer>,
+         filter: Res<LogFilter>| {
+            commands.entity(*id).despawn_related::<Children>();
+            // newest first
+            let records = buffer
+                .iter()
+                .rev()
+                .filter(|record| filter.matches(record))
+                .take(MAX_SHOWN_RECORDS);
+            for record in records {
+                commands.ui_builder(*id).spawn_scene(
+                    ("tabs_log", "log_record"),
+                    &mut scene_builder,
+                    |row| {
+                        row.get("time").update_text(record.time_label());
+                        row.get("level")
+                            .insert(TextColor(level_color(&record.level)))
+                            .update_text(record.level.to_string());
+                        row.get("target").update_text(record.target.clone());
+                        row.get("message").update_text(record.message.clone());
+                    },
+                );
+            }
+        },
+    );
+}
+
+pub(crate) fn log_tab_plugin(app: &mut App) {
+    app.init_resource::<LogFilter>().add_systems(
+        FixedUpdate,
+        (
+            // new records arrive every frame, so refresh at a readable pace
+            super::broadcast_fn(UpdateLogView).run_if(
+                on_timer(std::time::Duration::from_millis(500))
+                    .and(resource_changed::<LogBuffer>)
+                    .and(|filter: Res<LogFilter>| filter.paused_at.is_none()),
+            ),
+            super::broadcast_fn(UpdateLogView).run_if(resource_changed::<LogFilter>),
+        )
+            .run_if(in_state(super::AppTab::Log)),
+    );
+}
@@ -1,21 +1,116 @@
 use std::env;
 use std::ffi::OsStr;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 use bevy::prelude::*;
 use bevy::ui::RelativeCursorPosition;
+use bevy::window::{PrimaryWindow, WindowResized};
 use bevy_cobweb::prelude::*;
 use bevy_cobweb_ui::prelude::*;
 use itertools::Itertools;
 
+use crate::command_registry::{AddCommandExt, KeyBinding, RegisteredCommand};
 use crate::config::ICON_CONFIG;
-use crate::fs::EntryType;
-use crate::resources::{CurrentDirectory, DirectoryEntries};
+use crate::fs::{EntryType, ResolvedEntry};
+use crate::resources::{ActiveTab, CurrentDirectory, DirectoryEntries, EntrySelection,
+                       ExplorerTabs, LocationBar, OverviewMode};
+use crate::search::{LineMatch, SearchResults};
+use crate::settings::Settings;
 use crate::traits::{PathChecksExt, WithUiIcon};
+use crate::ui::bookmarks_tab::toggle_bookmark;
+use crate::ui::context_menu::{ContextMenuKind, OpenContextMenu};
+use crate::ui::history::{HistoryDirection, open_history_dropdown};
 use crate::ui::ui_events::{LocationSelectionUpdated, UpdateLocationText, UpdateOverview,
-                           UpdatePreview};
+                           UpdatePanelLayout, UpdatePreview, UpdateSelectionStatus, UpdateSettings};
 use crate::ui::{ExplorerCommand, broadcast_fn};
 
+pub mod bookmarks_sidebar;
+pub mod breadcrumbs;
+pub mod columns;
+pub mod directory_tree;
+pub mod dual_pane;
+pub mod entry_actions;
+pub mod explorer_tabs;
+pub mod places;
 pub mod preview;
+pub mod search;
+
+pub(super) const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+/// spawning thousands of rows stalls the ui, more matches are only counted
+const MAX_SEARCH_RESULT_ROWS: usize = 1000;
+
+/// One of the buttons of an overview row
+#[derive(Component, Debug)]
+struct OverviewRow {
+    /// into [EntrySelection::rows]
+    index: usize,
+    /// what opening the entry does
+    open: Option<ExplorerCommand>,
+    /// shown as selected
+    selected: bool,
+}
+
+/// Selects the row like file managers do, plain clicks also `open` it
+fn on_row_pressed(
+    index: usize,
+    open: Option<ExplorerCommand>,
+) -> impl Fn(Commands, Res<ButtonInput<KeyCode>>, ActiveTab<&mut EntrySelection>) {
+    move |mut commands, keys, mut selection| {
+        let Some(mut selection) = selection.get_mut() else {
+            return;
+        };
+        let toggle = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+        let range = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        selection.click(index, toggle, range);
+        if !(toggle || range)
+            && let Some(open) = &open
+        {
+            commands.react().broadcast(open.clone());
+        }
+    }
+}
+
+/// Row index and time of the last primary click on a file, to detect double clicks
+#[derive(Debug, Default, Resource)]
+struct LastFileClick(Option<(usize, Instant)>);
+
+/// Right clicks open the menu for the row's entry type, selecting the row unless it already is,
+/// double clicks open files in their default application
+fn on_row_clicked(
+    index: usize,
+    entry_type: EntryType,
+) -> impl Fn(Trigger<Pointer<Click>>, Commands, ActiveTab<&mut EntrySelection>, ResMut<LastFileClick>)
+{
+    move |mut trigger, mut commands, mut selection, mut last_click| match trigger.event().button {
+        PointerButton::Secondary => {
+            trigger.propagate(false);
+            if let Some(mut selection) = selection.get_mut()
+                && !selection.is_selected(index)
+            {
+                selection.click(index, false, false);
+            }
+            commands.react().broadcast(OpenContextMenu {
+                kind: ContextMenuKind::Entry(entry_type),
+                position: trigger.event().pointer_location.position,
+            });
+        }
+        PointerButton::Primary if entry_type == EntryType::File => {
+            let now = Instant::now();
+            match last_click.0 {
+                Some((last_index, time))
+                    if last_index == index && now - time < DOUBLE_CLICK_TIME =>
+                {
+                    commands.run_system_cached(entry_actions::open_in_default_application);
+                    last_click.0 = None;
+                }
+                _ => last_click.0 = Some((index, now)),
+            }
+        }
+        _ => {}
+    }
+}
 
 fn setup_location_text<'a>(location: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
     assert!(location.path_ends_with(&["location"]));
@@ -34,13 +129,16 @@ fn setup_location_text<'a>(location: &mut SceneHandle<'a, UiBuilder<'a, Entity>>
         .observe(
             |trigger: Trigger<Pointer<DragStart>>,
              relcurpos: Query<&RelativeCursorPosition, With<Node>>,
-             current_directory: Res<CurrentDirectory>,
+             current_directory: ActiveTab<&CurrentDirectory>,
              mut commands: Commands| {
                 debug!("trigger DragStart");
                 if let Ok(rcp) = relcurpos.get(trigger.target())
                     && let Some(Vec2 { x, .. }) = rcp.normalized
                 {
-                    let cwd = current_directory.to_string();
+                    let cwd = current_directory
+                        .get()
+                        .map(ToString::to_string)
+                        .unwrap_or_default();
                     let index = (cwd.len() as f32 * x).floor() as usize;
                     let (before, selected, after) = split_string(&cwd, index);
                     commands
@@ -52,13 +150,16 @@ fn setup_location_text<'a>(location: &mut SceneHandle<'a, UiBuilder<'a, Entity>>
         .observe(
             |trigger: Trigger<Pointer<Drag>>,
              relcurpos: Query<&RelativeCursorPosition, With<Node>>,
-             current_directory: Res<CurrentDirectory>,
+             current_directory: ActiveTab<&CurrentDirectory>,
              mut commands: Commands| {
                 trace!("trigger Drag");
                 if let Ok(rcp) = relcurpos.get(trigger.target())
                     && let Some(Vec2 { x, .. }) = rcp.normalized
                 {
-                    let cwd = current_directory.to_string();
+                    let cwd = current_directory
+                        .get()
+                        .map(ToString::to_string)
+                        .unwrap_or_default();
                     let index = (cwd.len() as f32 * x).floor() as usize;
                     let (before, selected, after) = split_string(&cwd, index);
                     commands
@@ -70,13 +171,16 @@ fn setup_location_text<'a>(location: &mut SceneHandle<'a, UiBuilder<'a, Entity>>
         .observe(
             |trigger: Trigger<Pointer<DragEnd>>,
              relcurpos: Query<&RelativeCursorPosition, With<Node>>,
-             current_directory: Res<CurrentDirectory>,
+             current_directory: ActiveTab<&CurrentDirectory>,
              mut commands: Commands| {
                 info!("trigger DragEnd");
                 if let Ok(rcp) = relcurpos.get(trigger.target())
                     && let Some(Vec2 { x, .. }) = rcp.normalized
                 {
-                    let cwd = current_directory.to_string();
+                    let cwd = current_directory
+                        .get()
+                        .map(ToString::to_string)
+                        .unwrap_or_default();
                     let index = (cwd.len() as f32 * x).floor() as usize;
                     let (before, selected, after) = split_string(&cwd, index);
                     commands
@@ -87,8 +191,13 @@ fn setup_location_text<'a>(location: &mut SceneHandle<'a, UiBuilder<'a, Entity>>
         )
         .update_on(
             broadcast::<UpdateLocationText>(),
-            |_: TargetId, mut commands: Commands, current_directory: Res<CurrentDirectory>| {
-                let cwd = current_directory.to_string();
+            |_: TargetId,
+             mut commands: Commands,
+             current_directory: ActiveTab<&CurrentDirectory>| {
+                let cwd = current_directory
+                    .get()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
                 commands
                     .react()
                     .broadcast(LocationSelectionUpdated::new_no_selection(cwd));
@@ -118,6 +227,19 @@ fn setup_location_text<'a>(location: &mut SceneHandle<'a, UiBuilder<'a, Entity>>
     location.update(|_: TargetId, mut commands: Commands| {
         commands.react().broadcast(UpdateLocationText);
     });
+    location.update_on(
+        broadcast::<UpdateSettings>(),
+        |id: TargetId,
+         settings: Res<Settings>,
+         mut display_controls: Query<&mut DisplayControl>| {
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = match settings.location_bar {
+                    LocationBar::Text => DisplayControl::Show,
+                    LocationBar::Breadcrumbs => DisplayControl::Hide,
+                };
+            }
+        },
+    );
 }
 
 fn setup_navigation<'a>(navigation: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
@@ -144,6 +266,16 @@ fn setup_navigation<'a>(navigation: &mut SceneHandle<'a, UiBuilder<'a, Entity>>)
             ICON_CONFIG.navigation.reload,
             ExplorerCommand::Reload,
         ),
+        (
+            "home_button",
+            ICON_CONFIG.navigation.home,
+            ExplorerCommand::GotoHome,
+        ),
+        (
+            "root_button",
+            ICON_CONFIG.navigation.root,
+            ExplorerCommand::GotoRoot,
+        ),
     ];
 
     for (name, icon, explorer_command) in configs {
@@ -156,83 +288,384 @@ fn setup_navigation<'a>(navigation: &mut SceneHandle<'a, UiBuilder<'a, Entity>>)
                 commands.react().broadcast(explorer_command);
             });
     }
+    for (name, direction) in [
+        ("back_button", HistoryDirection::Back),
+        ("next_button", HistoryDirection::Next),
+    ] {
+        navigation.get(name).observe(
+            move |trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                if trigger.event().button == PointerButton::Secondary {
+                    let position = trigger.event().pointer_location.position;
+                    commands.run_system_cached_with(open_history_dropdown, (direction, position));
+                }
+            },
+        );
+    }
+    navigation
+        .get("places_button")
+        .update_text(ICON_CONFIG.navigation.places)
+        .observe(|trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+            if trigger.event().button == PointerButton::Primary {
+                let position = trigger.event().pointer_location.position;
+                commands.run_system_cached_with(
+                    places::open_places_menu,
+                    (position, places::well_known_places()),
+                );
+            }
+        });
+    navigation
+        .get("bookmark_button")
+        .update_text(ICON_CONFIG.navigation.bookmark)
+        .on_pressed(toggle_bookmark);
     setup_location_text(&mut navigation.get("location"));
+    breadcrumbs::setup_breadcrumbs(&mut navigation.get("breadcrumbs"));
+    breadcrumbs::setup_location_bar_button(&mut navigation.get("location_bar_button"));
+    columns::setup_overview_mode_button(&mut navigation.get("overview_mode_button"));
 }
 
 fn setup_header<'a>(header: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
     assert!(header.path_ends_with(&["header"]));
     setup_navigation(&mut header.get("navigation"));
+    search::setup_search_bar(&mut header.get("search"));
+}
+
+/// `label` is shown instead of the file name, split into stem and extension
+fn spawn_overview_item(
+    commands: &mut Commands,
+    scene_builder: &mut SceneBuilder,
+    items: Entity,
+    index: usize,
+    entry: &ResolvedEntry,
+    label: &Path,
+) {
+    let path = entry.path();
+    let entry_type = entry.entry_type();
+    let menu_command = match entry_type {
+        EntryType::File => Some(ExplorerCommand::SetPreview(Some(path.to_owned()))),
+        EntryType::Directory | EntryType::Symlink => {
+            Some(ExplorerCommand::SetDirectory(path.to_owned()))
+        }
+        _ => None,
+    };
+    let row = || OverviewRow {
+        index,
+        open: menu_command.clone(),
+        selected: false,
+    };
+    let mut builder = commands.ui_builder(items);
+    // spawn icon button
+    builder.spawn_scene(("widgets", "entry_button"), scene_builder, |icon_button| {
+        icon_button
+            .insert(row())
+            .on_pressed(on_row_pressed(index, None))
+            .observe(on_row_clicked(index, entry_type));
+        icon_button.get("text").update_text(entry_type.get_icon());
+    });
+    // spawn text button (filename)
+    builder.spawn_scene(
+        ("widgets", "entry_button"),
+        scene_builder,
+        |filename_button| {
+            filename_button
+                .insert((entry_type, row()))
+                .on_pressed(on_row_pressed(index, menu_command.clone()))
+                .observe(on_row_clicked(index, entry_type));
+            let stem = label.with_extension("");
+            if !stem.as_os_str().is_empty() {
+                filename_button
+                    .get("text")
+                    .update_text(stem.to_string_lossy());
+            } else {
+                // text still impacts width
+                filename_button.get("text").update_text("");
+                filename_button.insert(Visibility::Hidden);
+            }
+        },
+    );
+    // spawn text button (extension)
+    builder.spawn_scene(("widgets", "entry_button"), scene_builder, |ext_button| {
+        ext_button
+            .insert(row())
+            .on_pressed(on_row_pressed(index, None))
+            .observe(on_row_clicked(index, entry_type));
+        if let Some(ext) = label.extension().map(OsStr::to_string_lossy) {
+            ext_button.get("text").update_text(ext);
+        } else {
+            // text still impacts width
+            ext_button.get("text").update_text("");
+            ext_button.insert(Visibility::Hidden);
+        }
+    });
+}
+
+fn spawn_line_item(
+    commands: &mut Commands,
+    scene_builder: &mut SceneBuilder,
+    items: Entity,
+    path: &Path,
+    line: &LineMatch,
+) {
+    let command = ExplorerCommand::PreviewLine(path.to_owned(), line.number);
+    let mut builder = commands.ui_builder(items);
+    builder.spawn_scene(("widgets", "button"), scene_builder, |number_button| {
+        number_button.on_pressed(broadcast_fn(command.clone()));
+        number_button
+            .get("text")
+            .update_text(line.number.to_string());
+    });
+    builder.spawn_scene(("widgets", "button"), scene_builder, |text_button| {
+        text_button.on_pressed(broadcast_fn(command.clone()));
+        text_button.get("text").update_text(line.text.clone());
+    });
+    // keep the grid aligned
+    builder.spawn(Node::default());
 }
 
 pub fn init_main_tab<'a>(sh: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
     info!("init_main_tab ({:?})", env::current_dir());
+    explorer_tabs::setup_explorer_tabs(&mut sh.get("explorer_tabs"));
     setup_header(&mut sh.get("header"));
+    bookmarks_sidebar::setup_bookmarks_sidebar(&mut sh.get("body::bookmarks"));
+    directory_tree::setup_directory_tree(&mut sh.get("body::tree"));
 
-    sh.get("content::overview::items").update_on(
+    // overview and preview
+    sh.get("body::content").update_on(
+        broadcast::<UpdatePanelLayout>(),
+        |id: TargetId,
+         settings: Res<Settings>,
+         window: Single<&Window, With<PrimaryWindow>>,
+         mut nodes: Query<&mut Node>| {
+            if let Ok(mut node) = nodes.get_mut(*id) {
+                node.flex_direction = match settings.overview_mode {
+                    // the preview is the last column
+                    OverviewMode::Columns => FlexDirection::Row,
+                    OverviewMode::Grid => settings.panel_layout.flex_direction(window.size()),
+                };
+            }
+        },
+    );
+    columns::setup_columns(&mut sh.get("body::content::columns"));
+    dual_pane::setup_dual_pane(&mut sh.get("body::content::panes"));
+
+    sh.get("body::content::panes::overview").observe(
+        |trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+            if trigger.event().button == PointerButton::Secondary {
+                commands.react().broadcast(OpenContextMenu {
+                    kind: ContextMenuKind::Background,
+                    position: trigger.event().pointer_location.position,
+                });
+            }
+        },
+    );
+
+    sh.get("body::content::panes::overview::items").update_on(
         broadcast::<UpdateOverview>(),
         |id: TargetId,
          mut commands: Commands,
          mut scene_builder: SceneBuilder,
-         entries: Res<DirectoryEntries>| {
-            info!("content::overview::items on broadcast UpdateOverview");
+         search_results: Res<SearchResults>,
+         mut tab: ActiveTab<(&DirectoryEntries, &mut EntrySelection)>| {
+            info!("content::panes::overview::items on broadcast UpdateOverview");
             commands.entity(*id).despawn_related::<Children>();
+            let Some((entries, mut selection)) = tab.get_mut() else {
+                return;
+            };
+            let mut listed = vec![];
 
-            let mut entries: Vec<_> = entries.clone();
-            entries.sort();
-
-            for entry in entries {
-                let path = entry.path();
-                let entry_type = entry.entry_type();
-                let menu_command = match entry_type {
-                    EntryType::File => Some(ExplorerCommand::SetPreview(Some(path.to_owned()))),
-                    EntryType::Directory | EntryType::Symlink => {
-                        Some(ExplorerCommand::SetDirectory(path.to_owned()))
+            if search_results.is_active() {
+                // search results stay in the order they were found
+                let mut rows = 0;
+                for (index, hit) in search_results.hits.iter().enumerate() {
+                    if rows >= MAX_SEARCH_RESULT_ROWS {
+                        break;
                     }
-                    _ => None,
-                };
-                let mut builder = commands.ui_builder(*id);
-                // spawn icon button
-                builder.spawn_scene(("widgets", "button"), &mut scene_builder, |icon_button| {
-                    icon_button.on_pressed(|| {
-                        info!("overview-item[icon]: on_pressed not implemented!");
-                    });
-                    icon_button.get("text").update_text(entry_type.get_icon());
-                });
-                // spawn text button (filename)
-                builder.spawn_scene(
-                    ("widgets", "button"),
-                    &mut scene_builder,
-                    |filename_button| {
-                        filename_button.insert(entry_type);
-                        if let Some(menu_command) = menu_command {
-                            filename_button.on_pressed(broadcast_fn(menu_command));
-                        }
-                        if let Some(name) = path.file_stem().map(OsStr::to_string_lossy) {
-                            filename_button.get("text").update_text(name);
-                        } else {
-                            // text still impacts width
-                            filename_button.get("text").update_text("");
-                            filename_button.insert(Visibility::Hidden);
-                        }
-                    },
-                );
-                // spawn text button (extension)
-                builder.spawn_scene(("widgets", "button"), &mut scene_builder, |ext_button| {
-                    ext_button.on_pressed(|| {
-                        info!("overview-item[ext]: on_pressed not implemented!");
-                    });
-                    if let Some(ext) = path.extension().map(OsStr::to_string_lossy) {
-                        ext_button.get("text").update_text(ext);
-                    } else {
-                        // text still impacts width
-                        ext_button.get("text").update_text("");
-                        ext_button.insert(Visibility::Hidden);
+                    let entry = &hit.entry;
+                    listed.push(entry.path().to_owned());
+                    let label = entry
+                        .path()
+                        .strip_prefix(&search_results.root)
+                        .unwrap_or(entry.path());
+                    spawn_overview_item(
+                        &mut commands,
+                        &mut scene_builder,
+                        *id,
+                        index,
+                        entry,
+                        label,
+                    );
+                    rows += 1;
+                    // matching lines are listed below their file, as long as rows are left
+                    for line in hit.lines.iter().take(MAX_SEARCH_RESULT_ROWS - rows) {
+                        spawn_line_item(&mut commands, &mut scene_builder, *id, entry.path(), line);
+                        rows += 1;
                     }
-                });
+                }
+            } else {
+                let mut entries = entries.to_vec();
+                entries.sort();
+
+                for (index, entry) in entries.iter().enumerate() {
+                    listed.push(entry.path().to_owned());
+                    let name = entry.path().file_name().map_or(entry.path(), Path::new);
+                    spawn_overview_item(&mut commands, &mut scene_builder, *id, index, entry, name);
+                }
             }
+            selection.set_rows(listed);
         },
     );
 
-    sh.get("content::preview::scroll::view_shim::view::shim")
+    sh.get("body::content::preview::scroll::view_shim::view::shim")
         .update_on(broadcast::<UpdatePreview>(), preview::update_preview);
 }
+
+fn move_focus(
+    In((step, extend)): In<(isize, bool)>,
+    mut selection: ActiveTab<&mut EntrySelection>,
+) {
+    if let Some(mut selection) = selection.get_mut() {
+        selection.move_focus(step, extend);
+    }
+}
+
+/// Runs `edit` on the selection of the active tab
+fn edit_selection(
+    In(edit): In<fn(&mut EntrySelection)>,
+    mut selection: ActiveTab<&mut EntrySelection>,
+) {
+    if let Some(mut selection) = selection.get_mut() {
+        edit(&mut selection);
+    }
+}
+
+fn open_focused_entry(
+    mut commands: Commands,
+    selection: ActiveTab<&EntrySelection>,
+    rows: Query<&OverviewRow>,
+) {
+    let Some(selection) = selection.get() else {
+        return;
+    };
+    if let Some(open) = rows
+        .iter()
+        .find(|row| Some(row.index) == selection.focused())
+        .and_then(|row| row.open.clone())
+    {
+        commands.react().broadcast(open);
+    }
+}
+
+fn show_entry_selection(
+    mut commands: Commands,
+    selection: ActiveTab<&EntrySelection>,
+    mut rows: Query<(Entity, &mut OverviewRow)>,
+) {
+    let Some(selection) = selection.get() else {
+        return;
+    };
+    for (e, mut row) in &mut rows {
+        let selected = selection.is_selected(row.index);
+        if selected != row.selected {
+            row.selected = selected;
+            if selected {
+                commands.react().entity_event(e, Select);
+            } else {
+                commands.react().entity_event(e, Deselect);
+            }
+        }
+        if selection.focused() == Some(row.index) {
+            commands
+                .entity(e)
+                .insert(Outline::new(Val::Px(1.0), Val::ZERO, Color::WHITE));
+        } else {
+            commands.entity(e).remove::<Outline>();
+        }
+    }
+}
+
+pub(crate) fn main_tab_plugin(app: &mut App) {
+    let move_focus_command = |name, key_binding, step, extend| {
+        RegisteredCommand::new(name, move |commands| {
+            commands.run_system_cached_with(move_focus, (step, extend));
+        })
+        .bind(key_binding)
+    };
+    app.init_resource::<LastFileClick>()
+        .add_command(move_focus_command(
+            "Move to next entry",
+            KeyBinding::new(KeyCode::ArrowDown),
+            1,
+            false,
+        ))
+        .add_command(move_focus_command(
+            "Move to previous entry",
+            KeyBinding::new(KeyCode::ArrowUp),
+            -1,
+            false,
+        ))
+        .add_command(move_focus_command(
+            "Extend selection down",
+            KeyBinding::new(KeyCode::ArrowDown).shift(),
+            1,
+            true,
+        ))
+        .add_command(move_focus_command(
+            "Extend selection up",
+            KeyBinding::new(KeyCode::ArrowUp).shift(),
+            -1,
+            true,
+        ))
+        .add_command(
+            RegisteredCommand::new("Select all", |commands| {
+                commands.run_system_cached_with(
+                    edit_selection,
+                    EntrySelection::select_all as fn(&mut EntrySelection),
+                );
+            })
+            .bind(KeyBinding::new(KeyCode::KeyA).ctrl()),
+        )
+        .add_command(
+            RegisteredCommand::new("Invert selection", |commands| {
+                commands.run_system_cached_with(
+                    edit_selection,
+                    EntrySelection::invert as fn(&mut EntrySelection),
+                );
+            })
+            .bind(KeyBinding::new(KeyCode::KeyI).ctrl()),
+        )
+        .add_command(
+            RegisteredCommand::new("Clear selection", |commands| {
+                commands.run_system_cached_with(
+                    edit_selection,
+                    EntrySelection::clear as fn(&mut EntrySelection),
+                );
+            })
+            .bind(KeyBinding::new(KeyCode::Escape)),
+        )
+        .add_command(
+            RegisteredCommand::new("Open entry", |commands| {
+                commands.run_system_cached(open_focused_entry);
+            })
+            .bind(KeyBinding::new(KeyCode::Enter)),
+        )
+        .add_command(
+            RegisteredCommand::new("Toggle breadcrumbs", |commands| {
+                commands.run_system_cached(breadcrumbs::toggle_location_bar);
+            })
+            .bind(KeyBinding::new(KeyCode::KeyL).ctrl()),
+        )
+        .add_systems(
+            Update,
+            (
+                show_entry_selection.run_if(
+                    any_match_filter::<Changed<EntrySelection>>
+                        .or(any_match_filter::<Added<OverviewRow>>),
+                ),
+                broadcast_fn(UpdateSelectionStatus).run_if(
+                    any_match_filter::<Changed<EntrySelection>>
+                        .or(resource_changed::<ExplorerTabs>),
+                ),
+                broadcast_fn(UpdatePanelLayout)
+                    .run_if(resource_changed::<Settings>.or(on_event::<WindowResized>)),
+            ),
+        );
+}
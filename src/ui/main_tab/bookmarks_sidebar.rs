@@ -0,0 +1,53 @@
+use crate::bookmarks::Bookmarks;
+use crate::prelude::*;
+use crate::resources::{ActiveTab, CurrentDirectory};
+use crate::ui::bookmarks_tab::open_bookmark;
+use crate::ui::ui_events::UpdateBookmarks;
+
+/// Lists the bookmarks, the one of the current directory is selected
+pub fn setup_bookmarks_sidebar<'a>(sidebar: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    sidebar.update_on(
+        broadcast::<UpdateBookmarks>(),
+        |id: TargetId,
+         bookmarks: Res<Bookmarks>,
+         mut display_controls: Query<&mut DisplayControl>| {
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = match bookmarks.is_empty() {
+                    true => DisplayControl::Hide,
+                    false => DisplayControl::Show,
+                };
+            }
+        },
+    );
+
+    sidebar.get("items").update_on(
+        broadcast::<UpdateBookmarks>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         bookmarks: Res<Bookmarks>,
+         directory: ActiveTab<&CurrentDirectory>| {
+            commands.entity(*id).despawn_related::<Children>();
+            let current = directory.get().map(|directory| directory.to_path_buf());
+            for bookmark in bookmarks.iter() {
+                let path = bookmark.path.clone();
+                let label = bookmark.label().into_owned();
+                let selected = current.as_ref() == Some(&path);
+                commands.ui_builder(*id).spawn_scene(
+                    ("widgets", "entry_button"),
+                    &mut scene_builder,
+                    |button| {
+                        button.on_pressed(move |mut commands: Commands| {
+                            commands.run_system_cached_with(open_bookmark, path.clone());
+                        });
+                        button.get("text").update_text(label);
+                        if selected {
+                            let e = button.id();
+                            button.react().entity_event(e, Select);
+                        }
+                    },
+                );
+            }
+        },
+    );
+}
@@ -0,0 +1,216 @@
+use std::fs;
+use std::ops::Range;
+
+use crate::config::ICON_CONFIG;
+use crate::prelude::*;
+use crate::resources::{ActiveTab, CurrentDirectory, LocationBar};
+use crate::settings::Settings;
+use crate::ui::ExplorerCommand;
+use crate::ui::main_tab::places::{Place, open_places_menu};
+use crate::ui::ui_events::UpdateSettings;
+
+/// long paths collapse in the middle until the labels of the breadcrumbs fit
+const MAX_BREADCRUMB_CHARS: usize = 60;
+
+fn label(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => path.to_string_lossy().into_owned(),
+    }
+}
+
+/// `directory` and all its ancestors, the root first
+fn crumbs(directory: &Path) -> Vec<(String, PathBuf)> {
+    let mut crumbs = directory
+        .ancestors()
+        .map(|path| (label(path), path.to_owned()))
+        .collect_vec();
+    crumbs.reverse();
+    crumbs
+}
+
+/// The breadcrumbs shown as one "...", growing from the middle.
+/// The root and the current directory are always shown
+fn collapsed(crumbs: &[(String, PathBuf)]) -> Range<usize> {
+    let width = |i: usize| crumbs[i].0.chars().count();
+    let mut total: usize = (0..crumbs.len()).map(width).sum();
+    let middle = crumbs.len() / 2;
+    let mut hidden = middle..middle;
+    while total > MAX_BREADCRUMB_CHARS {
+        let can_grow_end = hidden.end + 1 < crumbs.len();
+        let can_grow_start = hidden.start > 1;
+        if can_grow_end && (hidden.end - middle <= middle - hidden.start || !can_grow_start) {
+            total -= width(hidden.end);
+            hidden.end += 1;
+        } else if can_grow_start {
+            hidden.start -= 1;
+            total -= width(hidden.start);
+        } else {
+            break;
+        }
+    }
+    hidden
+}
+
+/// The directories in the parent of `path`, including `path`
+fn siblings(path: &Path) -> Vec<Place> {
+    let Some(entries) = path.parent().and_then(|parent| fs::read_dir(parent).ok()) else {
+        return vec![];
+    };
+    entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_dir())
+        .sorted()
+        .map(|path| (label(&path), ExplorerCommand::SetDirectory(path)))
+        .collect()
+}
+
+/// Opens the dropdown at the click with the places `places` returns then
+fn open_on_click(
+    places: impl Fn() -> Vec<Place> + Send + Sync + 'static,
+) -> impl Fn(Trigger<Pointer<Click>>, Commands) {
+    move |trigger, mut commands| {
+        if trigger.event().button == PointerButton::Primary {
+            let position = trigger.event().pointer_location.position;
+            commands.run_system_cached_with(open_places_menu, (position, places()));
+        }
+    }
+}
+
+pub fn toggle_location_bar(mut settings: ResMut<Settings>) {
+    settings.location_bar = settings.location_bar.toggled();
+}
+
+pub fn setup_location_bar_button<'a>(button: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    button.on_pressed(toggle_location_bar);
+    button.get("text").update_on(
+        broadcast::<UpdateSettings>(),
+        |id: TargetId, mut commands: Commands, settings: Res<Settings>| {
+            // what clicking switches to
+            let icon = match settings.location_bar {
+                LocationBar::Breadcrumbs => ICON_CONFIG.navigation.location_text,
+                LocationBar::Text => ICON_CONFIG.navigation.breadcrumbs,
+            };
+            commands.ui_builder(*id).update_text(icon);
+        },
+    );
+}
+
+/// A button per component of the current directory, each with a dropdown of its siblings
+pub fn setup_breadcrumbs<'a>(breadcrumbs: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    breadcrumbs.update_on(
+        broadcast::<UpdateSettings>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         settings: Res<Settings>,
+         directory: ActiveTab<&CurrentDirectory>,
+         mut display_controls: Query<&mut DisplayControl>| {
+            let shown = settings.location_bar == LocationBar::Breadcrumbs;
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = match shown {
+                    true => DisplayControl::Show,
+                    false => DisplayControl::Hide,
+                };
+            }
+            commands.entity(*id).despawn_related::<Children>();
+            let (true, Some(directory)) = (shown, directory.get()) else {
+                return;
+            };
+            let crumbs = crumbs(directory);
+            let hidden = collapsed(&crumbs);
+            let mut hidden_places = (!hidden.is_empty()).then(|| {
+                crumbs[hidden.clone()]
+                    .iter()
+                    .map(|(label, path)| {
+                        (label.clone(), ExplorerCommand::SetDirectory(path.clone()))
+                    })
+                    .collect_vec()
+            });
+            for (i, (label, path)) in crumbs.iter().enumerate() {
+                if !hidden.contains(&i) {
+                    spawn_breadcrumb(&mut commands, &mut scene_builder, *id, label, path);
+                } else if let Some(places) = hidden_places.take() {
+                    commands.ui_builder(*id).spawn_scene(
+                        ("tabs_main", "breadcrumbs_collapsed"),
+                        &mut scene_builder,
+                        |collapsed| {
+                            collapsed.observe(open_on_click(move || places.clone()));
+                        },
+                    );
+                }
+            }
+        },
+    );
+}
+
+fn spawn_breadcrumb(
+    commands: &mut Commands,
+    scene_builder: &mut SceneBuilder,
+    breadcrumbs: Entity,
+    label: &str,
+    path: &Path,
+) {
+    commands.ui_builder(breadcrumbs).spawn_scene(
+        ("tabs_main", "breadcrumb"),
+        scene_builder,
+        |breadcrumb| {
+            let opened = path.to_owned();
+            let mut open = breadcrumb.get("open");
+            open.on_pressed(move |mut commands: Commands| {
+                commands
+                    .react()
+                    .broadcast(ExplorerCommand::SetDirectory(opened.clone()));
+            });
+            open.get("text").update_text(label.to_owned());
+            let mut siblings_button = breadcrumb.get("siblings");
+            if path.parent().is_some() {
+                let path = path.to_owned();
+                siblings_button.observe(open_on_click(move || siblings(&path)));
+            } else {
+                // the root has none
+                siblings_button.insert(DisplayControl::Hide);
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hidden(path: &str) -> Range<usize> {
+        collapsed(&crumbs(Path::new(path)))
+    }
+
+    #[test]
+    fn short_paths_are_not_collapsed() {
+        assert_eq!(hidden("/"), 0..0);
+        assert_eq!(hidden("/home"), 1..1);
+        assert_eq!(hidden("/home/user/Documents"), 2..2);
+    }
+
+    #[test]
+    fn long_paths_collapse_in_the_middle() {
+        let path = format!("/{}", ["directory"; 10].join("/"));
+        let crumbs = crumbs(Path::new(&path));
+        let hidden = collapsed(&crumbs);
+        assert!(!hidden.is_empty());
+        assert!(hidden.start >= 1 && hidden.end < crumbs.len());
+        let shown: usize = (0..crumbs.len())
+            .filter(|i| !hidden.contains(i))
+            .map(|i| crumbs[i].0.chars().count())
+            .sum();
+        assert!(shown <= MAX_BREADCRUMB_CHARS);
+    }
+
+    #[test]
+    fn root_and_current_are_always_shown() {
+        let huge = "x".repeat(MAX_BREADCRUMB_CHARS * 2);
+        assert_eq!(hidden(&format!("/{huge}")), 1..1);
+        assert_eq!(hidden(&format!("/a/{huge}")), 1..2);
+        // a huge ancestor is hidden first, the rest of the path fits then
+        assert_eq!(hidden(&format!("/{huge}/a/b/c")), 1..3);
+        assert_eq!(hidden(&format!("/a/b/c/{huge}")), 1..4);
+    }
+}
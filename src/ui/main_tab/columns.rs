@@ -0,0 +1,222 @@
+use crate::bridge::{ActiveTabChanged, CurrentDirectoryChanged};
+use crate::command_registry::{AddCommandExt, KeyBinding, RegisteredCommand};
+use crate::config::ICON_CONFIG;
+use crate::fs::{EntryType, FsCommand};
+use crate::prelude::*;
+use crate::resources::{ActiveTab, CurrentDirectory, DirectoryListings, EntrySelection,
+                       OverviewMode};
+use crate::settings::Settings;
+use crate::traits::WithUiIcon;
+use crate::ui::main_tab::preview::ScrollIntoView;
+use crate::ui::ui_events::{UpdateColumns, UpdateSettings};
+use crate::ui::{ExplorerCommand, broadcast_fn};
+
+/// ancestors of the current directory shown as columns, the nearest ones
+const MAX_ANCESTOR_COLUMNS: usize = 3;
+
+/// The directory that was left for its parent, selected once the parent is listed
+#[derive(Debug, Default, Resource)]
+struct LeftDirectory(Option<PathBuf>);
+
+/// The ancestors shown as columns, the farthest first
+fn ancestor_columns(directory: &Path) -> Vec<&Path> {
+    let mut ancestors = directory
+        .ancestors()
+        .skip(1)
+        .take(MAX_ANCESTOR_COLUMNS)
+        .collect_vec();
+    ancestors.reverse();
+    ancestors
+}
+
+pub fn toggle_overview_mode(mut settings: ResMut<Settings>) {
+    settings.overview_mode = settings.overview_mode.toggled();
+}
+
+pub fn setup_overview_mode_button<'a>(button: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    button.on_pressed(toggle_overview_mode);
+    button.get("text").update_on(
+        broadcast::<UpdateSettings>(),
+        |id: TargetId, mut commands: Commands, settings: Res<Settings>| {
+            // what clicking switches to
+            let icon = match settings.overview_mode {
+                OverviewMode::Grid => ICON_CONFIG.navigation.columns,
+                OverviewMode::Columns => ICON_CONFIG.navigation.grid,
+            };
+            commands.ui_builder(*id).update_text(icon);
+        },
+    );
+}
+
+/// A column per ancestor of the current directory, the child on the way there is selected.
+/// The overview is the column of the current directory
+pub fn setup_columns<'a>(columns: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    columns.update_on(
+        broadcast::<UpdateColumns>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         settings: Res<Settings>,
+         listings: Res<DirectoryListings>,
+         directory: ActiveTab<&CurrentDirectory>,
+         mut display_controls: Query<&mut DisplayControl>| {
+            let shown = settings.overview_mode == OverviewMode::Columns;
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = match shown {
+                    true => DisplayControl::Show,
+                    false => DisplayControl::Hide,
+                };
+            }
+            commands.entity(*id).despawn_related::<Children>();
+            let (true, Some(directory)) = (shown, directory.get()) else {
+                return;
+            };
+            for ancestor in ancestor_columns(directory) {
+                let mut entries = listings.get(ancestor).unwrap_or_default().to_vec();
+                entries.sort();
+                commands.ui_builder(*id).spawn_scene(
+                    ("tabs_main", "column"),
+                    &mut scene_builder,
+                    |column| {
+                        let mut shim = column.get("view::shim");
+                        for entry in &entries {
+                            let path = entry.path().to_owned();
+                            let on_the_way = directory.starts_with(&path);
+                            let open = match entry.entry_type() {
+                                EntryType::File => ExplorerCommand::SetPreview(Some(path.clone())),
+                                _ => ExplorerCommand::SetDirectory(path.clone()),
+                            };
+                            let label = format!(
+                                "{} {}",
+                                entry.entry_type().get_icon(),
+                                path.file_name().unwrap_or_default().to_string_lossy()
+                            );
+                            shim.spawn_scene(("widgets", "entry_button"), |button| {
+                                button.on_pressed(broadcast_fn(open));
+                                button.get("text").update_text(label);
+                                if on_the_way {
+                                    let e = button.id();
+                                    button.insert(ScrollIntoView);
+                                    button.react().entity_event(e, Select);
+                                }
+                            });
+                        }
+                    },
+                );
+            }
+        },
+    );
+}
+
+/// Lists the ancestors shown as columns that are not yet, and forgets the ones no longer shown
+fn list_ancestors(
+    settings: Res<Settings>,
+    mut listed: Local<Vec<PathBuf>>,
+    mut listings: ResMut<DirectoryListings>,
+    directory: ActiveTab<&CurrentDirectory>,
+    mut fs_commands: EventWriter<FsCommand>,
+) {
+    let shown = match (settings.overview_mode, directory.get()) {
+        (OverviewMode::Columns, Some(directory)) => ancestor_columns(directory)
+            .into_iter()
+            .map(Path::to_owned)
+            .collect(),
+        _ => vec![],
+    };
+    for path in listed.iter().filter(|path| !shown.contains(path)) {
+        listings.forget(path);
+    }
+    for path in &shown {
+        if !listings.is_requested(path) {
+            fs_commands.write(FsCommand::List(path.clone()));
+        }
+    }
+    *listed = shown;
+}
+
+/// Goes to the parent of the current directory in the column view
+fn move_to_previous_column(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    directory: ActiveTab<&CurrentDirectory>,
+    mut left_directory: ResMut<LeftDirectory>,
+) {
+    let (OverviewMode::Columns, Some(directory)) = (settings.overview_mode, directory.get()) else {
+        return;
+    };
+    if let Some(parent) = directory.parent() {
+        left_directory.0 = Some(directory.to_path_buf());
+        commands
+            .react()
+            .broadcast(ExplorerCommand::SetDirectory(parent.to_owned()));
+    }
+}
+
+/// Opens the focused directory in the column view
+fn move_to_next_column(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    selection: ActiveTab<&EntrySelection>,
+) {
+    if settings.overview_mode != OverviewMode::Columns {
+        return;
+    }
+    if let Some(focused) = selection
+        .get()
+        .and_then(|selection| selection.focused_path())
+        .filter(|path| path.is_dir())
+    {
+        commands
+            .react()
+            .broadcast(ExplorerCommand::SetDirectory(focused.to_owned()));
+    }
+}
+
+fn select_left_directory(
+    mut left_directory: ResMut<LeftDirectory>,
+    mut selection: ActiveTab<&mut EntrySelection>,
+) {
+    let (Some(left), Some(mut selection)) = (&left_directory.0, selection.get_mut()) else {
+        return;
+    };
+    if let Some(index) = selection.rows().iter().position(|row| row == left) {
+        selection.click(index, false, false);
+        left_directory.0 = None;
+    }
+}
+
+pub(crate) fn columns_plugin(app: &mut App) {
+    app.init_resource::<LeftDirectory>()
+        .add_command(RegisteredCommand::new("Toggle column view", |commands| {
+            commands.run_system_cached(toggle_overview_mode);
+        }))
+        .add_command(
+            RegisteredCommand::new("Move to previous column", |commands| {
+                commands.run_system_cached(move_to_previous_column);
+            })
+            .bind(KeyBinding::new(KeyCode::ArrowLeft)),
+        )
+        .add_command(
+            RegisteredCommand::new("Move to next column", |commands| {
+                commands.run_system_cached(move_to_next_column);
+            })
+            .bind(KeyBinding::new(KeyCode::ArrowRight)),
+        )
+        .add_systems(
+            Update,
+            (
+                // listings the directory tree forgot are requested again
+                list_ancestors.run_if(
+                    resource_changed::<Settings>
+                        .or(resource_changed::<DirectoryListings>)
+                        .or(on_event::<CurrentDirectoryChanged>)
+                        .or(on_event::<ActiveTabChanged>),
+                ),
+                broadcast_fn(UpdateColumns)
+                    .run_if(resource_changed::<Settings>.or(resource_changed::<DirectoryListings>)),
+                select_left_directory
+                    .run_if(|left_directory: Res<LeftDirectory>| left_directory.0.is_some()),
+            )
+                .chain(),
+        );
+}
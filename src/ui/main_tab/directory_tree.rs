@@ -0,0 +1,376 @@
+use std::collections::BTreeSet;
+
+use crate::bridge::{ActiveTabChanged, CurrentDirectoryChanged};
+use crate::command_registry::{AddCommandExt, KeyBinding, RegisteredCommand};
+use crate::fs::{EntryType, FsCommand};
+use crate::prelude::*;
+use crate::resources::{ActiveTab, CurrentDirectory, DirectoryListings, EntrySelection};
+use crate::settings::Settings;
+use crate::ui::main_tab::OverviewRow;
+use crate::ui::main_tab::preview::ScrollIntoView;
+use crate::ui::ui_events::{UpdateDirectoryTree, UpdateSettings};
+use crate::ui::{ExplorerCommand, broadcast_fn};
+
+/// indentation per level of the tree, in pixels
+const INDENT: f32 = 12.0;
+
+/// The directories of the tree that are expanded, and the one the keyboard moves from
+#[derive(Debug, Default, Resource)]
+pub struct DirectoryTree {
+    expanded: BTreeSet<PathBuf>,
+    /// follows the current directory
+    cursor: Option<PathBuf>,
+    /// scroll to the cursor when the rows are built the next time
+    reveal: bool,
+}
+
+/// The name button of a row, to find what was dragged onto another row
+#[derive(Component, Debug)]
+struct TreeNode(PathBuf);
+
+/// The listed subdirectories of `path`, sorted
+fn subdirectories(listings: &DirectoryListings, path: &Path) -> Vec<PathBuf> {
+    listings
+        .get(path)
+        .unwrap_or_default()
+        .iter()
+        .filter(|entry| entry.entry_type() == EntryType::Directory)
+        .map(|entry| entry.path().to_owned())
+        .sorted()
+        .collect()
+}
+
+impl DirectoryTree {
+    fn is_expanded(&self, path: &Path) -> bool {
+        self.expanded.contains(path)
+    }
+    /// Every shown directory with its depth, from the root down
+    fn rows(&self, listings: &DirectoryListings) -> Vec<(PathBuf, usize)> {
+        let mut rows = vec![];
+        let mut stack = vec![(PathBuf::from("/"), 0)];
+        while let Some((path, depth)) = stack.pop() {
+            if self.is_expanded(&path) {
+                for child in subdirectories(listings, &path).into_iter().rev() {
+                    stack.push((child, depth + 1));
+                }
+            }
+            rows.push((path, depth));
+        }
+        rows
+    }
+    /// Expands the ancestors of `directory` and moves the cursor there
+    fn reveal(&mut self, directory: &Path) {
+        self.expanded
+            .extend(directory.ancestors().skip(1).map(Path::to_owned));
+        self.cursor = Some(directory.to_owned());
+        self.reveal = true;
+    }
+    /// Collapses `path` and everything below it, `false` if it was not expanded
+    fn collapse(&mut self, path: &Path) -> bool {
+        let expanded = self.expanded.remove(path);
+        self.expanded.retain(|expanded| !expanded.starts_with(path));
+        expanded
+    }
+    fn toggle(&mut self, path: &Path) {
+        if !self.collapse(path) {
+            self.expanded.insert(path.to_owned());
+        }
+    }
+    /// Moves the cursor `step` rows, starting at the root
+    fn move_cursor(&mut self, step: isize, listings: &DirectoryListings) {
+        let rows = self.rows(listings);
+        let index = self
+            .cursor
+            .as_ref()
+            .and_then(|cursor| rows.iter().position(|(path, _)| path == cursor))
+            .map_or(0, |index| {
+                index.saturating_add_signed(step).min(rows.len() - 1)
+            });
+        self.cursor = Some(rows[index].0.clone());
+        self.reveal = true;
+    }
+    /// Expands the cursor, or moves to its first subdirectory when it already is
+    fn expand_cursor(&mut self, listings: &DirectoryListings) {
+        let Some(cursor) = self.cursor.clone() else {
+            return;
+        };
+        if !self.expanded.insert(cursor.clone())
+            && let Some(first) = subdirectories(listings, &cursor).into_iter().next()
+        {
+            self.cursor = Some(first);
+            self.reveal = true;
+        }
+    }
+    /// Collapses the cursor, or moves to its parent when it already is
+    fn collapse_cursor(&mut self) {
+        let Some(cursor) = self.cursor.clone() else {
+            return;
+        };
+        if !self.collapse(&cursor)
+            && let Some(parent) = cursor.parent()
+        {
+            self.cursor = Some(parent.to_owned());
+            self.reveal = true;
+        }
+    }
+}
+
+/// Moves what was dragged onto a row into its directory, or copies it while Ctrl is held.
+/// Dragging a selected overview row drags the whole selection
+fn drop_onto_tree_node(
+    trigger: Trigger<Pointer<DragDrop>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    rows: Query<&OverviewRow>,
+    nodes: Query<&TreeNode>,
+    selection: ActiveTab<&EntrySelection>,
+    mut fs_commands: EventWriter<FsCommand>,
+) {
+    let Ok(TreeNode(to)) = nodes.get(trigger.target()) else {
+        return;
+    };
+    let dropped = trigger.event().dropped;
+    let paths = if let Ok(row) = rows.get(dropped) {
+        let Some(selection) = selection.get() else {
+            return;
+        };
+        match selection.is_selected(row.index) {
+            true => selection.selected_paths().map(Path::to_owned).collect(),
+            false => selection
+                .rows()
+                .get(row.index)
+                .cloned()
+                .into_iter()
+                .collect(),
+        }
+    } else if let Ok(TreeNode(path)) = nodes.get(dropped) {
+        vec![path.clone()]
+    } else {
+        return;
+    };
+    // dropping onto the directory they are in, or onto themselves, does nothing
+    let paths = paths
+        .into_iter()
+        .filter(|path| path != to && path.parent() != Some(to.as_path()))
+        .collect_vec();
+    if paths.is_empty() {
+        return;
+    }
+    let to = to.clone();
+    fs_commands.write(
+        match keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+            true => FsCommand::Copy { paths, to },
+            false => FsCommand::Move { paths, to },
+        },
+    );
+}
+
+pub fn setup_directory_tree<'a>(tree: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    tree.update_on(
+        broadcast::<UpdateSettings>(),
+        |id: TargetId,
+         settings: Res<Settings>,
+         mut display_controls: Query<&mut DisplayControl>| {
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = match settings.directory_tree {
+                    true => DisplayControl::Show,
+                    false => DisplayControl::Hide,
+                };
+            }
+        },
+    );
+
+    tree.get("view::shim").update_on(
+        broadcast::<UpdateDirectoryTree>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         mut tree: ResMut<DirectoryTree>,
+         listings: Res<DirectoryListings>,
+         directory: ActiveTab<&CurrentDirectory>| {
+            commands.entity(*id).despawn_related::<Children>();
+            let current = directory.get().map(|directory| directory.to_path_buf());
+            // building the rows is no change to the tree
+            let reveal = std::mem::take(&mut tree.bypass_change_detection().reveal);
+            for (path, depth) in tree.rows(&listings) {
+                let toggle = match (tree.is_expanded(&path), listings.get(&path)) {
+                    (true, _) => "-",
+                    (false, Some(entries))
+                        if !entries
+                            .iter()
+                            .any(|entry| entry.entry_type() == EntryType::Directory) =>
+                    {
+                        ""
+                    }
+                    (false, _) => "+",
+                };
+                let label = match path.file_name() {
+                    Some(name) => name.to_string_lossy().into_owned(),
+                    None => path.to_string_lossy().into_owned(),
+                };
+                let is_current = current.as_ref() == Some(&path);
+                let is_cursor = tree.cursor.as_ref() == Some(&path);
+                commands.ui_builder(*id).spawn_scene(
+                    ("tabs_main", "tree_row"),
+                    &mut scene_builder,
+                    |row| {
+                        row.update(move |id: TargetId, mut nodes: Query<&mut Node>| {
+                            if let Ok(mut node) = nodes.get_mut(*id) {
+                                node.margin.left = Val::Px(INDENT * depth as f32);
+                            }
+                        });
+
+                        let mut toggle_button = row.get("toggle");
+                        toggle_button.get("text").update_text(toggle);
+                        let toggled = path.clone();
+                        toggle_button.on_pressed(move |mut tree: ResMut<DirectoryTree>| {
+                            tree.toggle(&toggled);
+                        });
+
+                        let mut name = row.get("name");
+                        name.get("text").update_text(label);
+                        let opened = path.clone();
+                        name.on_pressed(move |mut commands: Commands| {
+                            commands
+                                .react()
+                                .broadcast(ExplorerCommand::SetDirectory(opened.clone()));
+                        })
+                        .observe(drop_onto_tree_node)
+                        .insert(TreeNode(path.clone()));
+                        if is_current {
+                            let e = name.id();
+                            name.react().entity_event(e, Select);
+                        }
+                        if is_cursor {
+                            name.insert(Outline::new(Val::Px(1.0), Val::ZERO, Color::BLACK));
+                            if reveal {
+                                name.insert(ScrollIntoView);
+                            }
+                        }
+                    },
+                );
+            }
+        },
+    );
+}
+
+fn reveal_current_directory(
+    mut tree: ResMut<DirectoryTree>,
+    directory: ActiveTab<&CurrentDirectory>,
+) {
+    if let Some(directory) = directory.get() {
+        tree.reveal(directory);
+    }
+}
+
+/// Lists the expanded directories that are not yet, and forgets the collapsed ones.
+/// Nothing is shown while the tree is hidden
+fn list_expanded_directories(
+    tree: Res<DirectoryTree>,
+    settings: Res<Settings>,
+    mut listed: Local<BTreeSet<PathBuf>>,
+    mut listings: ResMut<DirectoryListings>,
+    mut fs_commands: EventWriter<FsCommand>,
+) {
+    let shown = match settings.directory_tree {
+        true => tree.expanded.clone(),
+        false => default(),
+    };
+    for path in listed.difference(&shown) {
+        listings.forget(path);
+    }
+    for path in &shown {
+        if !listings.is_requested(path) {
+            fs_commands.write(FsCommand::List(path.clone()));
+        }
+    }
+    *listed = shown;
+}
+
+fn move_tree_cursor(
+    In(step): In<isize>,
+    mut tree: ResMut<DirectoryTree>,
+    listings: Res<DirectoryListings>,
+) {
+    tree.move_cursor(step, &listings);
+}
+
+fn expand_tree_cursor(mut tree: ResMut<DirectoryTree>, listings: Res<DirectoryListings>) {
+    tree.expand_cursor(&listings);
+}
+
+fn collapse_tree_cursor(mut tree: ResMut<DirectoryTree>) {
+    tree.collapse_cursor();
+}
+
+fn open_tree_cursor(mut commands: Commands, tree: Res<DirectoryTree>) {
+    if let Some(cursor) = &tree.cursor {
+        commands
+            .react()
+            .broadcast(ExplorerCommand::SetDirectory(cursor.clone()));
+    }
+}
+
+fn toggle_directory_tree(mut settings: ResMut<Settings>) {
+    settings.directory_tree = !settings.directory_tree;
+}
+
+pub(crate) fn directory_tree_plugin(app: &mut App) {
+    let move_cursor_command = |name, key, step| {
+        RegisteredCommand::new(name, move |commands| {
+            commands.run_system_cached_with(move_tree_cursor, step);
+        })
+        .bind(KeyBinding::new(key).ctrl())
+    };
+    app.init_resource::<DirectoryTree>()
+        .add_command(
+            RegisteredCommand::new("Toggle directory tree", |commands| {
+                commands.run_system_cached(toggle_directory_tree);
+            })
+            .bind(KeyBinding::new(KeyCode::KeyB).ctrl()),
+        )
+        .add_command(move_cursor_command(
+            "Move down in directory tree",
+            KeyCode::ArrowDown,
+            1,
+        ))
+        .add_command(move_cursor_command(
+            "Move up in directory tree",
+            KeyCode::ArrowUp,
+            -1,
+        ))
+        .add_command(
+            RegisteredCommand::new("Expand in directory tree", |commands| {
+                commands.run_system_cached(expand_tree_cursor);
+            })
+            .bind(KeyBinding::new(KeyCode::ArrowRight).ctrl()),
+        )
+        .add_command(
+            RegisteredCommand::new("Collapse in directory tree", |commands| {
+                commands.run_system_cached(collapse_tree_cursor);
+            })
+            .bind(KeyBinding::new(KeyCode::ArrowLeft).ctrl()),
+        )
+        .add_command(
+            RegisteredCommand::new("Open from directory tree", |commands| {
+                commands.run_system_cached(open_tree_cursor);
+            })
+            .bind(KeyBinding::new(KeyCode::Enter).ctrl()),
+        )
+        .add_systems(
+            Update,
+            (
+                reveal_current_directory
+                    .run_if(on_event::<CurrentDirectoryChanged>.or(on_event::<ActiveTabChanged>)),
+                // listings the column view forgot are requested again
+                list_expanded_directories.run_if(
+                    resource_changed::<DirectoryTree>
+                        .or(resource_changed::<Settings>)
+                        .or(resource_changed::<DirectoryListings>),
+                ),
+                broadcast_fn(UpdateDirectoryTree).run_if(
+                    resource_changed::<DirectoryTree>.or(resource_changed::<DirectoryListings>),
+                ),
+            )
+                .chain(),
+        );
+}
@@ -0,0 +1,233 @@
+use std::ffi::OsStr;
+
+use bevy::window::PrimaryWindow;
+
+use crate::command_registry::{AddCommandExt, KeyBinding, RegisteredCommand};
+use crate::fs::FsCommand;
+use crate::notifications::Notification;
+use crate::prelude::*;
+use crate::resources::{ActiveTab, CurrentDirectory, DirectoryEntries, EntrySelection, ExplorerTab,
+                       ExplorerTabs};
+use crate::settings::Settings;
+use crate::traits::WithUiIcon;
+use crate::ui::broadcast_fn;
+use crate::ui::main_tab::explorer_tabs::activate_tab;
+use crate::ui::ui_events::{UpdateOtherPane, UpdatePanelLayout};
+
+fn toggle_dual_pane(
+    mut commands: Commands,
+    mut tabs: ResMut<ExplorerTabs>,
+    directories: Query<&CurrentDirectory, With<ExplorerTab>>,
+) {
+    if let Some(pane) = tabs.other_pane() {
+        commands.entity(pane).despawn();
+        tabs.set_other_pane(None);
+    } else if let Some(directory) = tabs.active().and_then(|tab| directories.get(tab).ok()) {
+        let pane = commands
+            .spawn((ExplorerTab, CurrentDirectory::from(directory.to_path_buf())))
+            .id();
+        tabs.set_other_pane(Some(pane));
+    }
+}
+
+/// `switch` moves to the other pane, otherwise the panes trade directories
+fn exchange_panes(In(switch): In<bool>, mut commands: Commands, mut tabs: ResMut<ExplorerTabs>) {
+    if tabs.other_pane().is_none() {
+        return;
+    }
+    if switch {
+        tabs.switch_pane();
+    } else {
+        tabs.swap_panes();
+    }
+    if let Some(active) = tabs.active() {
+        commands.run_system_cached_with(activate_tab, active);
+    }
+}
+
+/// Copies or `move`s the selection of the active pane into the directory of the other one
+fn transfer_to_other_pane(
+    In(move_entries): In<bool>,
+    tabs: Res<ExplorerTabs>,
+    selection: ActiveTab<&EntrySelection>,
+    directories: Query<&CurrentDirectory, With<ExplorerTab>>,
+    mut fs_commands: EventWriter<FsCommand>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(to) = tabs
+        .other_pane()
+        .and_then(|pane| directories.get(pane).ok())
+        .map(|directory| directory.to_path_buf())
+    else {
+        notifications.write(Notification::warning("There is no other pane"));
+        return;
+    };
+    let Some(selection) = selection.get() else {
+        return;
+    };
+    let paths = selection
+        .targets()
+        .into_iter()
+        .map(Path::to_owned)
+        .collect_vec();
+    if paths.is_empty() {
+        return;
+    }
+    fs_commands.write(match move_entries {
+        true => FsCommand::Move { paths, to },
+        false => FsCommand::Copy { paths, to },
+    });
+}
+
+fn spawn_other_pane_item(
+    commands: &mut Commands,
+    scene_builder: &mut SceneBuilder,
+    items: Entity,
+    icon: String,
+    path: &Path,
+) {
+    let name = path.file_name().map_or(path, Path::new);
+    let stem = name.with_extension("").to_string_lossy().into_owned();
+    let extension = name
+        .extension()
+        .map(OsStr::to_string_lossy)
+        .unwrap_or_default()
+        .into_owned();
+    let mut builder = commands.ui_builder(items);
+    for text in [icon, stem, extension] {
+        builder.spawn_scene(("widgets", "entry_button"), scene_builder, |button| {
+            if text.is_empty() {
+                // text still impacts width
+                button.insert(Visibility::Hidden);
+            }
+            button.get("text").update_text(text);
+        });
+    }
+}
+
+/// The panes follow the panel layout, the other pane may come first
+fn update_panes_direction(
+    id: TargetId,
+    tabs: Res<ExplorerTabs>,
+    settings: Res<Settings>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut nodes: Query<&mut Node>,
+) {
+    if let Ok(mut node) = nodes.get_mut(*id) {
+        node.flex_direction = match (
+            settings.panel_layout.flex_direction(window.size()),
+            tabs.other_pane_first(),
+        ) {
+            (FlexDirection::Column, true) => FlexDirection::ColumnReverse,
+            (_, true) => FlexDirection::RowReverse,
+            (direction, false) => direction,
+        };
+    }
+}
+
+pub fn setup_dual_pane<'a>(panes: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    panes
+        .update_on(broadcast::<UpdateOtherPane>(), update_panes_direction)
+        .update_on(broadcast::<UpdatePanelLayout>(), update_panes_direction);
+
+    let mut other_pane = panes.get("other_pane");
+    other_pane
+        .update_on(
+            broadcast::<UpdateOtherPane>(),
+            |id: TargetId,
+             tabs: Res<ExplorerTabs>,
+             mut display_controls: Query<&mut DisplayControl>| {
+                if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                    *display_control = match tabs.other_pane() {
+                        Some(_) => DisplayControl::Show,
+                        None => DisplayControl::Hide,
+                    };
+                }
+            },
+        )
+        // the other pane is only listed, clicking it makes it the active one
+        .observe(|trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+            if trigger.event().button == PointerButton::Primary {
+                commands.run_system_cached_with(exchange_panes, true);
+            }
+        });
+
+    other_pane.get("title").update_on(
+        broadcast::<UpdateOtherPane>(),
+        |id: TargetId,
+         mut commands: Commands,
+         tabs: Res<ExplorerTabs>,
+         directories: Query<&CurrentDirectory, With<ExplorerTab>>| {
+            if let Some(directory) = tabs
+                .other_pane()
+                .and_then(|pane| directories.get(pane).ok())
+            {
+                commands.ui_builder(*id).update_text(directory.to_string());
+            }
+        },
+    );
+
+    other_pane.get("items").update_on(
+        broadcast::<UpdateOtherPane>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         tabs: Res<ExplorerTabs>,
+         entries: Query<&DirectoryEntries, With<ExplorerTab>>| {
+            commands.entity(*id).despawn_related::<Children>();
+            let Some(entries) = tabs.other_pane().and_then(|pane| entries.get(pane).ok()) else {
+                return;
+            };
+            let mut entries = entries.to_vec();
+            entries.sort();
+            for entry in &entries {
+                spawn_other_pane_item(
+                    &mut commands,
+                    &mut scene_builder,
+                    *id,
+                    entry.entry_type().get_icon().to_string(),
+                    entry.path(),
+                );
+            }
+        },
+    );
+}
+
+pub(crate) fn dual_pane_plugin(app: &mut App) {
+    app.add_command(
+        RegisteredCommand::new("Toggle dual pane", |commands| {
+            commands.run_system_cached(toggle_dual_pane);
+        })
+        .bind(KeyBinding::new(KeyCode::F9)),
+    )
+    .add_command(
+        RegisteredCommand::new("Switch pane", |commands| {
+            commands.run_system_cached_with(exchange_panes, true);
+        })
+        .bind(KeyBinding::new(KeyCode::Tab)),
+    )
+    .add_command(
+        RegisteredCommand::new("Swap panes", |commands| {
+            commands.run_system_cached_with(exchange_panes, false);
+        })
+        .bind(KeyBinding::new(KeyCode::KeyU).ctrl()),
+    )
+    .add_command(
+        RegisteredCommand::new("Copy to other pane", |commands| {
+            commands.run_system_cached_with(transfer_to_other_pane, false);
+        })
+        .bind(KeyBinding::new(KeyCode::F5).shift()),
+    )
+    .add_command(
+        RegisteredCommand::new("Move to other pane", |commands| {
+            commands.run_system_cached_with(transfer_to_other_pane, true);
+        })
+        .bind(KeyBinding::new(KeyCode::F6)),
+    )
+    .add_systems(
+        Update,
+        broadcast_fn(UpdateOtherPane).run_if(
+            resource_changed::<ExplorerTabs>.or(any_match_filter::<Changed<DirectoryEntries>>),
+        ),
+    );
+}
@@ -0,0 +1,252 @@
+use std::env;
+use std::process::Command as Process;
+
+use chrono::{DateTime, Local};
+
+use crate::clipboard::CopyToClipboard;
+use crate::command_registry::{AddCommandExt, KeyBinding, RegisteredCommand};
+use crate::fs::FsCommand;
+use crate::mime_apps::desktop_entry::DesktopEntry;
+use crate::mime_apps::{MimeApps, spawn_detached};
+use crate::notifications::Notification;
+use crate::prelude::*;
+use crate::resources::{ActiveTab, CurrentDirectory, EntrySelection};
+
+/// tried in order when `$TERMINAL` is not set
+const TERMINALS: &[&str] = &["x-terminal-emulator", "gnome-terminal", "konsole", "xterm"];
+
+/// One path per line
+fn copy_paths(selection: ActiveTab<&EntrySelection>, mut clipboard: EventWriter<CopyToClipboard>) {
+    let Some(selection) = selection.get() else {
+        return;
+    };
+    let paths = selection.targets();
+    if !paths.is_empty() {
+        clipboard.write(CopyToClipboard(
+            paths.iter().map(|path| path.to_string_lossy()).join("\n"),
+        ));
+    }
+}
+
+/// `command` is a program with optional arguments, the paths are appended
+fn open_with(
+    In(command): In<String>,
+    selection: ActiveTab<&EntrySelection>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(selection) = selection.get() else {
+        return;
+    };
+    let mut words = command.split_whitespace();
+    let Some(program) = words.next() else {
+        return;
+    };
+    let paths = selection.targets();
+    if let Err(e) = spawn_detached(Process::new(program).args(words).args(&paths)) {
+        notifications.write(Notification::error(format!("Cannot run {program}: {e}")));
+    }
+}
+
+/// Each target is opened by the default application for its mime type
+pub(super) fn open_in_default_application(
+    selection: ActiveTab<&EntrySelection>,
+    mime_apps: Res<MimeApps>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(selection) = selection.get() else {
+        return;
+    };
+    let mut launches: Vec<(&DesktopEntry, Vec<PathBuf>)> = vec![];
+    for path in selection.targets() {
+        let mime_type = mime_apps.mime_type(path);
+        let Some(application) = mime_apps.default_application(&mime_type) else {
+            notifications.write(Notification::warning(format!(
+                "No application opens {mime_type} files like {}",
+                path.display()
+            )));
+            continue;
+        };
+        match launches
+            .iter_mut()
+            .find(|(launched, _)| launched.id == application.id)
+        {
+            Some((_, paths)) => paths.push(path.to_owned()),
+            None => launches.push((application, vec![path.to_owned()])),
+        }
+    }
+    for (application, paths) in launches {
+        if let Err(e) = application.launch(&paths) {
+            notifications.write(Notification::error(format!(
+                "Cannot run {}: {e}",
+                application.name
+            )));
+        }
+    }
+}
+
+fn rename(
+    In(name): In<String>,
+    selection: ActiveTab<&EntrySelection>,
+    mut fs_commands: EventWriter<FsCommand>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(selection) = selection.get() else {
+        return;
+    };
+    let Some(from) = selection.focused_path() else {
+        return;
+    };
+    let name = name.trim();
+    if name.is_empty() || name.contains(std::path::MAIN_SEPARATOR) {
+        notifications.write(Notification::warning(format!(
+            "{name:?} is not a valid file name"
+        )));
+        return;
+    }
+    fs_commands.write(FsCommand::Rename {
+        from: from.to_owned(),
+        to: from.with_file_name(name),
+    });
+}
+
+fn delete(
+    In(confirmation): In<String>,
+    selection: ActiveTab<&EntrySelection>,
+    mut fs_commands: EventWriter<FsCommand>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(selection) = selection.get() else {
+        return;
+    };
+    if confirmation.trim() != "yes" {
+        notifications.write(Notification::info("nothing was deleted"));
+        return;
+    }
+    let paths = selection
+        .targets()
+        .into_iter()
+        .map(Path::to_owned)
+        .collect_vec();
+    notifications.write(Notification::info(format!(
+        "deleting {} entries",
+        paths.len()
+    )));
+    fs_commands.write(FsCommand::Delete(paths));
+}
+
+fn show_properties(
+    selection: ActiveTab<&EntrySelection>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(selection) = selection.get() else {
+        return;
+    };
+    let Some(path) = selection.focused_path() else {
+        return;
+    };
+    let metadata = match path.symlink_metadata() {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            notifications.write(Notification::error(format!(
+                "Cannot read {}: {e}",
+                path.display()
+            )));
+            return;
+        }
+    };
+    let kind = if metadata.is_symlink() {
+        "symlink"
+    } else if metadata.is_dir() {
+        "directory"
+    } else {
+        "file"
+    };
+    let modified = metadata.modified().map_or_else(
+        |_| "unknown".to_owned(),
+        |time| {
+            DateTime::<Local>::from(time)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        },
+    );
+    let read_only = if metadata.permissions().readonly() {
+        ", read-only"
+    } else {
+        ""
+    };
+    notifications.write(Notification::info(format!(
+        "{}: {kind}, {} bytes, modified {modified}{read_only}",
+        path.display(),
+        metadata.len()
+    )));
+}
+
+fn open_terminal_here(
+    current_directory: ActiveTab<&CurrentDirectory>,
+    mut notifications: EventWriter<Notification>,
+) {
+    let Some(current_directory) = current_directory.get() else {
+        return;
+    };
+    let configured = env::var("TERMINAL").ok();
+    let started = configured
+        .iter()
+        .map(String::as_str)
+        .chain(TERMINALS.iter().copied())
+        .any(|terminal| {
+            spawn_detached(Process::new(terminal).current_dir(&**current_directory)).is_ok()
+        });
+    if !started {
+        notifications.write(Notification::error(
+            "No terminal found, set $TERMINAL to choose one",
+        ));
+    }
+}
+
+/// Commands acting on the selected entries, see [EntrySelection::targets]
+pub(crate) fn entry_actions_plugin(app: &mut App) {
+    app.add_command(
+        RegisteredCommand::new("Copy path", |commands| {
+            commands.run_system_cached(copy_paths);
+        })
+        .bind(KeyBinding::new(KeyCode::KeyC).ctrl().shift()),
+    )
+    .add_command(
+        RegisteredCommand::new("Open in default application", |commands| {
+            commands.run_system_cached(open_in_default_application);
+        })
+        .bind(KeyBinding::new(KeyCode::Enter).shift()),
+    )
+    .add_command(RegisteredCommand::with_argument(
+        "Open with program",
+        "a program to open the selection with",
+        |commands, program| {
+            commands.run_system_cached_with(open_with, program.to_owned());
+        },
+    ))
+    .add_command(
+        RegisteredCommand::with_argument("Rename", "the new name", |commands, name| {
+            commands.run_system_cached_with(rename, name.to_owned());
+        })
+        .bind(KeyBinding::new(KeyCode::F2)),
+    )
+    .add_command(
+        RegisteredCommand::with_argument(
+            "Delete",
+            "yes to delete the selection permanently",
+            |commands, confirmation| {
+                commands.run_system_cached_with(delete, confirmation.to_owned());
+            },
+        )
+        .bind(KeyBinding::new(KeyCode::Delete)),
+    )
+    .add_command(
+        RegisteredCommand::new("Properties", |commands| {
+            commands.run_system_cached(show_properties);
+        })
+        .bind(KeyBinding::new(KeyCode::Enter).alt()),
+    )
+    .add_command(RegisteredCommand::new("Open terminal here", |commands| {
+        commands.run_system_cached(open_terminal_here);
+    }));
+}
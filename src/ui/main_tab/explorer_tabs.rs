@@ -0,0 +1,218 @@
+use std::env;
+
+use crate::bridge::ActiveTabChanged;
+use crate::command_registry::{AddCommandExt, KeyBinding, RegisteredCommand};
+use crate::navigation::LocationHistory;
+use crate::notifications::Notification;
+use crate::prelude::*;
+use crate::resources::{ActiveTab, ClosedTab, CurrentDirectory, EntrySelection, ExplorerTab,
+                       ExplorerTabs};
+use crate::ui::broadcast_fn;
+use crate::ui::ui_events::UpdateExplorerTabs;
+
+/// Makes `tab` the active tab, the working directory follows it
+pub(super) fn activate_tab(
+    In(tab): In<Entity>,
+    mut tabs: ResMut<ExplorerTabs>,
+    directories: Query<&CurrentDirectory, With<ExplorerTab>>,
+    mut active_tab_changed: EventWriter<ActiveTabChanged>,
+) {
+    if !tabs.activate(tab) {
+        return;
+    }
+    if let Ok(directory) = directories.get(tab)
+        && let Err(e) = env::set_current_dir(&**directory)
+    {
+        warn!("cannot change to {directory}: {e}");
+    }
+    active_tab_changed.write_default();
+}
+
+/// Opens `directory` in a new tab next to the active one
+fn open_tab(
+    In((directory, history)): In<(PathBuf, LocationHistory)>,
+    mut commands: Commands,
+    mut tabs: ResMut<ExplorerTabs>,
+) {
+    let tab = commands
+        .spawn((ExplorerTab, CurrentDirectory::from(directory), history))
+        .id();
+    let index = tabs.active().map(|_| tabs.active_index() + 1);
+    tabs.insert(index, tab);
+    commands.run_system_cached_with(activate_tab, tab);
+}
+
+fn new_tab(mut commands: Commands, directory: ActiveTab<&CurrentDirectory>) {
+    let Some(directory) = directory.get() else {
+        return;
+    };
+    commands.run_system_cached_with(open_tab, (directory.to_path_buf(), default()));
+}
+
+/// Opens the focused directory in a new tab
+fn open_focused_in_new_tab(mut commands: Commands, selection: ActiveTab<&EntrySelection>) {
+    if let Some(path) = selection.get().and_then(EntrySelection::focused_path)
+        && path.is_dir()
+    {
+        commands.run_system_cached_with(open_tab, (path.to_owned(), default()));
+    }
+}
+
+fn close_tab(
+    In(tab): In<Entity>,
+    mut commands: Commands,
+    mut tabs: ResMut<ExplorerTabs>,
+    closing: Query<(&CurrentDirectory, &LocationHistory), With<ExplorerTab>>,
+    mut notifications: EventWriter<Notification>,
+) {
+    if tabs.tabs().len() <= 1 {
+        notifications.write(Notification::warning("The last tab cannot be closed"));
+        return;
+    }
+    let (Some(index), Ok((directory, history))) =
+        (tabs.tabs().iter().position(|e| *e == tab), closing.get(tab))
+    else {
+        return;
+    };
+    tabs.close(
+        tab,
+        ClosedTab {
+            directory: directory.to_path_buf(),
+            history: history.clone(),
+            index,
+        },
+    );
+    commands.entity(tab).despawn();
+    if let Some(active) = tabs.active() {
+        commands.run_system_cached_with(activate_tab, active);
+    }
+}
+
+fn close_active_tab(mut commands: Commands, tabs: Res<ExplorerTabs>) {
+    if let Some(active) = tabs.active() {
+        commands.run_system_cached_with(close_tab, active);
+    }
+}
+
+/// Brings back the most recently closed tab where it was
+fn reopen_closed_tab(mut commands: Commands, mut tabs: ResMut<ExplorerTabs>) {
+    let Some(closed) = tabs.reopen() else {
+        return;
+    };
+    let tab = commands
+        .spawn((
+            ExplorerTab,
+            CurrentDirectory::from(closed.directory),
+            closed.history,
+        ))
+        .id();
+    tabs.insert(Some(closed.index), tab);
+    commands.run_system_cached_with(activate_tab, tab);
+}
+
+fn cycle_tabs(In(step): In<isize>, mut commands: Commands, mut tabs: ResMut<ExplorerTabs>) {
+    tabs.cycle(step);
+    if let Some(active) = tabs.active() {
+        commands.run_system_cached_with(activate_tab, active);
+    }
+}
+
+fn move_tab(In(step): In<isize>, mut tabs: ResMut<ExplorerTabs>) {
+    tabs.move_active(step);
+}
+
+pub fn setup_explorer_tabs<'a>(strip: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    strip.get("items").update_on(
+        broadcast::<UpdateExplorerTabs>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         tabs: Res<ExplorerTabs>,
+         directories: Query<&CurrentDirectory, With<ExplorerTab>>| {
+            commands.entity(*id).despawn_related::<Children>();
+            for &tab in tabs.tabs() {
+                let Ok(directory) = directories.get(tab) else {
+                    continue;
+                };
+                let title = directory.file_name().map_or(directory.to_string(), |name| {
+                    name.to_string_lossy().into_owned()
+                });
+                let active = tabs.active() == Some(tab);
+                commands.ui_builder(*id).spawn_scene(
+                    ("tabs_main", "explorer_tab"),
+                    &mut scene_builder,
+                    |item| {
+                        let mut title_button = item.get("title");
+                        title_button.on_pressed(move |mut commands: Commands| {
+                            commands.run_system_cached_with(activate_tab, tab);
+                        });
+                        title_button.get("text").update_text(title);
+                        if active {
+                            let e = title_button.id();
+                            title_button.react().entity_event(e, Select);
+                        }
+                        item.get("close").on_pressed(move |mut commands: Commands| {
+                            commands.run_system_cached_with(close_tab, tab);
+                        });
+                    },
+                );
+            }
+        },
+    );
+
+    strip.get("new_tab").on_pressed(new_tab);
+}
+
+pub(crate) fn explorer_tabs_plugin(app: &mut App) {
+    app.add_command(
+        RegisteredCommand::new("New tab", |commands| {
+            commands.run_system_cached(new_tab);
+        })
+        .bind(KeyBinding::new(KeyCode::KeyT).ctrl()),
+    )
+    .add_command(RegisteredCommand::new("Open in new tab", |commands| {
+        commands.run_system_cached(open_focused_in_new_tab);
+    }))
+    .add_command(
+        RegisteredCommand::new("Close tab", |commands| {
+            commands.run_system_cached(close_active_tab);
+        })
+        .bind(KeyBinding::new(KeyCode::KeyW).ctrl()),
+    )
+    .add_command(
+        RegisteredCommand::new("Reopen closed tab", |commands| {
+            commands.run_system_cached(reopen_closed_tab);
+        })
+        .bind(KeyBinding::new(KeyCode::KeyT).ctrl().shift()),
+    )
+    .add_command(
+        RegisteredCommand::new("Next explorer tab", |commands| {
+            commands.run_system_cached_with(cycle_tabs, 1);
+        })
+        .bind(KeyBinding::new(KeyCode::PageDown).ctrl()),
+    )
+    .add_command(
+        RegisteredCommand::new("Previous explorer tab", |commands| {
+            commands.run_system_cached_with(cycle_tabs, -1);
+        })
+        .bind(KeyBinding::new(KeyCode::PageUp).ctrl()),
+    )
+    .add_command(
+        RegisteredCommand::new("Move tab left", |commands| {
+            commands.run_system_cached_with(move_tab, -1);
+        })
+        .bind(KeyBinding::new(KeyCode::PageUp).ctrl().shift()),
+    )
+    .add_command(
+        RegisteredCommand::new("Move tab right", |commands| {
+            commands.run_system_cached_with(move_tab, 1);
+        })
+        .bind(KeyBinding::new(KeyCode::PageDown).ctrl().shift()),
+    )
+    .add_systems(
+        Update,
+        broadcast_fn(UpdateExplorerTabs).run_if(
+            resource_changed::<ExplorerTabs>.or(any_match_filter::<Changed<CurrentDirectory>>),
+        ),
+    );
+}
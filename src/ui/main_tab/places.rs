@@ -0,0 +1,136 @@
+use crate::places::{UserDir, mounted_volumes};
+use crate::prelude::*;
+use crate::ui::ui_events::UpdatePlacesMenu;
+use crate::ui::{ExplorerCommand, broadcast_fn};
+
+/// A label and where it goes
+pub type Place = (String, ExplorerCommand);
+
+/// The dropdown of the places button and the breadcrumbs, `Some` while open
+#[derive(Debug, Default, Resource)]
+pub struct PlacesMenu(Option<OpenPlacesMenu>);
+
+impl PlacesMenu {
+    pub fn is_open(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+#[derive(Debug)]
+pub struct OpenPlacesMenu {
+    /// where the window was clicked
+    position: Vec2,
+    places: Vec<Place>,
+}
+
+/// Shows `places` in a dropdown at `position`
+pub fn open_places_menu(
+    In((position, places)): In<(Vec2, Vec<Place>)>,
+    mut menu: ResMut<PlacesMenu>,
+) {
+    menu.0 = Some(OpenPlacesMenu { position, places });
+}
+
+/// Home, the user dirs that exist, the root and mounted volumes, read every time
+pub fn well_known_places() -> Vec<Place> {
+    let user_dirs = UserDir::ALL
+        .into_iter()
+        .filter(|user_dir| user_dir.path().is_some_and(|path| path.is_dir()))
+        .map(|user_dir| {
+            (
+                user_dir.name().to_owned(),
+                ExplorerCommand::GotoUserDir(user_dir),
+            )
+        });
+    let mounts = mounted_volumes().into_iter().map(|mount_point| {
+        (
+            mount_point.to_string_lossy().into_owned(),
+            ExplorerCommand::SetDirectory(mount_point),
+        )
+    });
+    std::iter::once(("Home".to_owned(), ExplorerCommand::GotoHome))
+        .chain(user_dirs)
+        .chain([("/".to_owned(), ExplorerCommand::GotoRoot)])
+        .chain(mounts)
+        .collect()
+}
+
+pub fn setup_places_menu<'a>(layer: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    layer
+        .update_on(
+            broadcast::<UpdatePlacesMenu>(),
+            |id: TargetId,
+             menu: Res<PlacesMenu>,
+             mut display_controls: Query<&mut DisplayControl>| {
+                if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                    *display_control = match menu.0.is_some() {
+                        true => DisplayControl::Show,
+                        false => DisplayControl::Hide,
+                    };
+                }
+            },
+        )
+        // clicks on places bubble up to here, clicks anywhere else only close the menu
+        .observe(
+            |trigger: Trigger<Pointer<Click>>,
+             mut commands: Commands,
+             mut menu: ResMut<PlacesMenu>,
+             explorer_commands: Query<&ExplorerCommand>,
+             parents: Query<&ChildOf>| {
+                let clicked = trigger.event().target;
+                if trigger.event().button == PointerButton::Primary
+                    && let Some(command) = std::iter::once(clicked)
+                        .chain(parents.iter_ancestors(clicked))
+                        .find_map(|e| explorer_commands.get(e).ok())
+                {
+                    commands.react().broadcast(command.clone());
+                }
+                menu.0 = None;
+            },
+        );
+
+    layer.get("menu").update_on(
+        broadcast::<UpdatePlacesMenu>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         menu: Res<PlacesMenu>,
+         mut nodes: Query<&mut Node>| {
+            commands.entity(*id).despawn_related::<Children>();
+            let Some(OpenPlacesMenu { position, places }) = &menu.0 else {
+                return;
+            };
+            for (label, command) in places {
+                commands.ui_builder(*id).spawn_scene(
+                    ("places", "place"),
+                    &mut scene_builder,
+                    |place| {
+                        place.insert(command.clone());
+                        place.get("text").update_text(label.clone());
+                    },
+                );
+            }
+            if let Ok(mut node) = nodes.get_mut(*id) {
+                node.left = Val::Px(position.x);
+                node.top = Val::Px(position.y);
+            }
+        },
+    );
+}
+
+fn close_on_escape(keys: Res<ButtonInput<KeyCode>>, mut menu: ResMut<PlacesMenu>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        menu.0 = None;
+    }
+}
+
+pub(crate) fn places_plugin(app: &mut App) {
+    app.init_resource::<PlacesMenu>().add_systems(
+        Update,
+        (
+            close_on_escape.run_if(|menu: Res<PlacesMenu>| menu.0.is_some()),
+            broadcast_fn(UpdatePlacesMenu).run_if(resource_changed::<PlacesMenu>),
+        )
+            .chain(),
+    );
+}
@@ -5,9 +5,12 @@ use std::path::Path;
 use bevy::asset::RenderAssetUsages;
 use bevy::color::palettes::css;
 use bevy::image::{CompressedImageFormats, ImageType};
+use bevy_cobweb_ui::builtin::widgets::scroll::{ScrollAxis, ScrollBar, ScrollShim, ScrollView};
+use bevy_cobweb_ui::builtin::widgets::slider::SliderValue;
 
+use crate::notifications::Notification;
 use crate::prelude::*;
-use crate::resources::PreviewPath;
+use crate::resources::{ActiveTab, PreviewLine, PreviewPath};
 
 #[derive(Clone, Copy, Debug)]
 enum PreviewMode<'a> {
@@ -23,14 +26,41 @@ enum ImageError {
     Texture(#[from] TextureError),
 }
 
-fn read_as_text<'a, P: AsRef<Path>>(path: P, mut builder: UiBuilder<'a, Entity>) -> io::Result<()> {
+/// Marks a node, like the highlighted line of a text preview, until it has been scrolled into view
+#[derive(Component, Debug)]
+pub(crate) struct ScrollIntoView;
+
+fn read_as_text<'a, P: AsRef<Path>>(
+    path: P,
+    highlight: Option<usize>,
+    mut builder: UiBuilder<'a, Entity>,
+) -> io::Result<()> {
     read_to_string(path).map(move |text| {
         // TODO: wrap/no-wrap mode for text
         // TODO: slider for font-size
+        let layout = TextLayout::default().with_linebreak(LineBreak::NoWrap);
+        let lines = text.lines().collect_vec();
+        let Some(index) = highlight
+            .map(|line| line.saturating_sub(1))
+            .filter(|index| *index < lines.len())
+        else {
+            builder.spawn((layout, Text::new(text)));
+            return;
+        };
+        // split the text so the highlighted line can have its own background
+        if index > 0 {
+            builder.spawn((layout, Text::new(lines[..index].join("\n"))));
+        }
         builder.spawn((
-            TextLayout::default().with_linebreak(LineBreak::NoWrap),
-            Text::new(text),
+            layout,
+            Text::new(lines[index]),
+            TextColor(css::BLACK.into()),
+            BackgroundColor(css::YELLOW.into()),
+            ScrollIntoView,
         ));
+        if index + 1 < lines.len() {
+            builder.spawn((layout, Text::new(lines[index + 1..].join("\n"))));
+        }
     })
 }
 
@@ -64,6 +94,7 @@ fn on_new_preview_path<P: AsRef<Path>>(
     commands: &mut Commands,
     images: &mut Assets<Image>,
     path: P,
+    highlight: Option<usize>,
 ) {
     let path = path.as_ref();
     let preview_mode = path
@@ -75,11 +106,15 @@ fn on_new_preview_path<P: AsRef<Path>>(
     let builder = commands.ui_builder(*id);
 
     match preview_mode {
-        PreviewMode::Text => read_as_text(path, builder).map_err(Into::into),
+        PreviewMode::Text => read_as_text(path, highlight, builder).map_err(Into::into),
         PreviewMode::Image(ext) => read_as_image(path, ext, images, builder),
     }
     // TODO: .or_else(|_| read_as_binary(path, commands.ui_builder(*id)))
     .unwrap_or_else(move |error| {
+        commands.send_event(Notification::warning(format!(
+            "Cannot preview {}: {error}",
+            path.display()
+        )));
         commands
             .ui_builder(*id)
             .spawn((Text::new(format!("{error}")), TextColor::from(css::RED)));
@@ -89,14 +124,88 @@ fn on_new_preview_path<P: AsRef<Path>>(
 pub fn update_preview(
     id: TargetId,
     mut commands: Commands,
-    preview_path: Res<PreviewPath>,
+    preview: ActiveTab<(&PreviewPath, &PreviewLine)>,
     mut images: ResMut<Assets<Image>>,
 ) {
-    info!("content::preview {preview_path:?}");
     // clear whatever we have now
     commands.entity(*id).despawn_related::<Children>();
+    let Some((preview_path, preview_line)) = preview.get() else {
+        return;
+    };
+    info!("content::preview {preview_path:?}");
     // build new preview, if required
     if let Some(path) = (*preview_path).as_ref() {
-        on_new_preview_path(id, &mut commands, &mut images, path);
+        on_new_preview_path(id, &mut commands, &mut images, path, **preview_line);
     }
 }
+
+/// Scroll shims and views, and whether the node is the view
+type ScrollNodes<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static ComputedNode,
+        &'static GlobalTransform,
+        Has<ScrollView>,
+    ),
+    Or<(With<ScrollShim>, With<ScrollView>)>,
+>;
+
+/// Moves the vertical scrollbars so the nodes marked with [ScrollIntoView] are centered
+fn scroll_to_highlighted_line(
+    mut commands: Commands,
+    lines: Query<(Entity, &ComputedNode, &GlobalTransform), With<ScrollIntoView>>,
+    scroll_nodes: ScrollNodes,
+    bars: Query<&ScrollBar>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    mut slider_values: ReactiveMut<SliderValue>,
+) {
+    for (entity, line_node, line_transform) in &lines {
+        if line_node.size().y == 0.0 {
+            // not laid out yet
+            continue;
+        }
+        commands.entity(entity).remove::<ScrollIntoView>();
+
+        let ancestors = parents.iter_ancestors(entity).collect_vec();
+        let Some((shim_node, shim_transform, _)) = ancestors
+            .iter()
+            .find_map(|e| scroll_nodes.get(*e).ok().filter(|(_, _, is_view)| !is_view))
+        else {
+            continue;
+        };
+        let Some((view_node, _, _)) = ancestors
+            .iter()
+            .find_map(|e| scroll_nodes.get(*e).ok().filter(|(_, _, is_view)| *is_view))
+        else {
+            continue;
+        };
+        // the scrollbar is a sibling of the view, or of one of its ancestors
+        let Some(bar) = ancestors.iter().find_map(|e| {
+            children
+                .get(*e)
+                .ok()?
+                .iter()
+                .find(|child| bars.get(*child).is_ok_and(|bar| bar.axis == ScrollAxis::Y))
+        }) else {
+            continue;
+        };
+
+        let scroll_size = shim_node.size().y - view_node.size().y;
+        if scroll_size <= 0.0 {
+            continue;
+        }
+        // both are centered, and move together while scrolling
+        let offset = (line_transform.translation().y - line_node.size().y / 2.0)
+            - (shim_transform.translation().y - shim_node.size().y / 2.0);
+        let centered = offset - (view_node.size().y - line_node.size().y) / 2.0;
+        if let Ok(value) = slider_values.get_mut(&mut commands, bar) {
+            *value = SliderValue::Single((centered / scroll_size).clamp(0.0, 1.0));
+        }
+    }
+}
+
+pub(crate) fn preview_plugin(app: &mut App) {
+    app.add_systems(Update, scroll_to_highlighted_line);
+}
@@ -0,0 +1,119 @@
+use crate::prelude::*;
+use crate::search::{SearchCommand, SearchOptions, SearchResults, SearchScope, SearchState};
+use crate::traits::PathChecksExt;
+use crate::ui::send_event_fn;
+use crate::ui::text_input::{TextInputCancelled, TextInputChanged, TextInputSubmitted,
+                            setup_text_input};
+use crate::ui::ui_events::UpdateOverview;
+
+fn status_text(results: &SearchResults) -> String {
+    let files = results.hits.len();
+    let matches = match results.scope {
+        SearchScope::Names => format!("{files} matches for {:?}", results.pattern),
+        SearchScope::Contents => {
+            let lines: usize = results.hits.iter().map(|hit| hit.lines.len()).sum();
+            format!("{lines} lines in {files} files for {:?}", results.pattern)
+        }
+    };
+    match results.state {
+        SearchState::Idle => String::new(),
+        SearchState::Running => format!("{matches}, searching..."),
+        SearchState::Done(duration) => {
+            format!("{matches} in {:.2}s", duration.as_secs_f32())
+        }
+        SearchState::Cancelled => format!("{matches}, cancelled"),
+    }
+}
+
+pub fn setup_search_bar<'a>(search: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    assert!(search.path_ends_with(&["search"]));
+
+    let mut query = search.get("query");
+    setup_text_input(&mut query, "search below here");
+    query.on_event::<TextInputSubmitted>().r(
+        |event: EntityEvent<TextInputSubmitted>,
+         mut search_commands: EventWriter<SearchCommand>| {
+            let (_, TextInputSubmitted(pattern)) = event.read();
+            search_commands.write(SearchCommand::Start(pattern.clone()));
+        },
+    );
+    query
+        .on_event::<TextInputCancelled>()
+        .r(send_event_fn(SearchCommand::Clear));
+
+    let mut mode_button = search.get("mode_button");
+    let mode_text = mode_button.get("text").id();
+    mode_button.on_pressed(
+        move |mut options: ResMut<SearchOptions>, mut text_editor: TextEditor| {
+            options.mode = options.mode.next();
+            write_text!(text_editor, mode_text, "{}", options.mode);
+        },
+    );
+    mode_button.get("text").update(
+        |id: TargetId, mut commands: Commands, options: Res<SearchOptions>| {
+            commands
+                .ui_builder(*id)
+                .update_text(options.mode.to_string());
+        },
+    );
+
+    let mut scope_button = search.get("scope_button");
+    let scope_text = scope_button.get("text").id();
+    scope_button.on_pressed(
+        move |mut options: ResMut<SearchOptions>, mut text_editor: TextEditor| {
+            options.scope = options.scope.next();
+            write_text!(text_editor, scope_text, "{}", options.scope);
+        },
+    );
+    scope_button.get("text").update(
+        |id: TargetId, mut commands: Commands, options: Res<SearchOptions>| {
+            commands
+                .ui_builder(*id)
+                .update_text(options.scope.to_string());
+        },
+    );
+
+    let mut max_depth = search.get("max_depth");
+    setup_text_input(&mut max_depth, "max depth");
+    max_depth.on_event::<TextInputChanged>().r(
+        |event: EntityEvent<TextInputChanged>, mut options: ResMut<SearchOptions>| {
+            let (_, TextInputChanged(value)) = event.read();
+            // anything that is not a number means unlimited
+            options.max_depth = value.trim().parse().ok();
+        },
+    );
+
+    search
+        .get("ignore_files")
+        .on_check(|mut options: ResMut<SearchOptions>| {
+            options.respect_ignore_files = true;
+        })
+        .on_uncheck(|mut options: ResMut<SearchOptions>| {
+            options.respect_ignore_files = false;
+        })
+        .update(
+            |id: TargetId, mut commands: Commands, options: Res<SearchOptions>| {
+                if options.respect_ignore_files {
+                    commands.react().entity_event(*id, Check);
+                }
+            },
+        );
+
+    search.get("cancel_button").on_pressed(
+        |results: Res<SearchResults>, mut search_commands: EventWriter<SearchCommand>| {
+            // first press stops the search, second press hides the results
+            search_commands.write(if results.state == SearchState::Running {
+                SearchCommand::Cancel
+            } else {
+                SearchCommand::Clear
+            });
+        },
+    );
+
+    search.get("status").update_on(
+        broadcast::<UpdateOverview>(),
+        |id: TargetId, mut commands: Commands, results: Res<SearchResults>| {
+            commands.ui_builder(*id).update_text(status_text(&results));
+        },
+    );
+}
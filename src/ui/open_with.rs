@@ -0,0 +1,185 @@
+use crate::command_registry::{AddCommandExt, RegisteredCommand};
+use crate::mime_apps::MimeApps;
+use crate::notifications::Notification;
+use crate::prelude::*;
+use crate::resources::{ActiveTab, EntrySelection};
+use crate::ui::broadcast_fn;
+use crate::ui::command_palette::run_command_by_name;
+use crate::ui::ui_events::UpdateOpenWith;
+
+/// Lists the applications for the mime type of the focused entry
+#[derive(Debug, Default, Resource)]
+pub(crate) struct OpenWith {
+    open: bool,
+    mime_type: String,
+    paths: Vec<PathBuf>,
+}
+
+impl OpenWith {
+    pub(crate) fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+fn launch(id: String) -> impl Fn(ResMut<OpenWith>, Res<MimeApps>, EventWriter<Notification>) {
+    move |mut open_with, mime_apps, mut notifications| {
+        let Some(application) = mime_apps.application(&id) else {
+            return;
+        };
+        if let Err(e) = application.launch(&open_with.paths) {
+            notifications.write(Notification::error(format!(
+                "Cannot run {}: {e}",
+                application.name
+            )));
+        }
+        open_with.open = false;
+    }
+}
+
+fn set_default(id: String) -> impl Fn(Res<OpenWith>, ResMut<MimeApps>, EventWriter<Notification>) {
+    move |open_with, mut mime_apps, mut notifications| {
+        match mime_apps.set_default(&open_with.mime_type, &id) {
+            Ok(()) => notifications.write(Notification::info(format!(
+                "{} files now open with {id}",
+                open_with.mime_type
+            ))),
+            Err(e) => notifications.write(Notification::error(format!(
+                "Cannot change the default application: {e}"
+            ))),
+        };
+    }
+}
+
+pub fn setup_open_with<'a>(popup: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    popup.update_on(
+        broadcast::<UpdateOpenWith>(),
+        |id: TargetId,
+         open_with: Res<OpenWith>,
+         mut display_controls: Query<&mut DisplayControl>| {
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = if open_with.open {
+                    DisplayControl::Show
+                } else {
+                    DisplayControl::Hide
+                };
+            }
+        },
+    );
+
+    popup.get("title").update_on(
+        broadcast::<UpdateOpenWith>(),
+        |id: TargetId, mut commands: Commands, open_with: Res<OpenWith>| {
+            let entries = match &open_with.paths[..] {
+                [path] => path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned(),
+                paths => format!("{} entries", paths.len()),
+            };
+            commands
+                .ui_builder(*id)
+                .update_text(format!("Open {entries} with"));
+        },
+    );
+
+    popup.get("applications").update_on(
+        broadcast::<UpdateOpenWith>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         open_with: Res<OpenWith>,
+         mime_apps: Res<MimeApps>| {
+            commands.entity(*id).despawn_related::<Children>();
+            if !open_with.open {
+                return;
+            }
+            let default = mime_apps.default_application(&open_with.mime_type);
+            for application in mime_apps.candidates(&open_with.mime_type) {
+                commands.ui_builder(*id).spawn_scene(
+                    ("open_with", "open_with_item"),
+                    &mut scene_builder,
+                    |item| {
+                        let mut open = item.get("open");
+                        open.on_pressed(launch(application.id.clone()));
+                        open.get("text").update_text(application.name.clone());
+                        let mut set_default_button = item.get("set_default");
+                        if default.is_some_and(|default| default.id == application.id) {
+                            set_default_button.get("text").update_text("default");
+                        } else {
+                            set_default_button.on_pressed(set_default(application.id.clone()));
+                        }
+                    },
+                );
+            }
+        },
+    );
+
+    popup
+        .get("other")
+        .on_pressed(|mut commands: Commands, mut open_with: ResMut<OpenWith>| {
+            open_with.open = false;
+            commands.run_system_cached_with(run_command_by_name, "Open with program".to_owned());
+        });
+
+    popup.get("status").update_on(
+        broadcast::<UpdateOpenWith>(),
+        |id: TargetId,
+         mut commands: Commands,
+         open_with: Res<OpenWith>,
+         mime_apps: Res<MimeApps>| {
+            let count = mime_apps.candidates(&open_with.mime_type).len();
+            commands
+                .ui_builder(*id)
+                .update_text(format!("{} - {count} applications", open_with.mime_type));
+        },
+    );
+}
+
+fn open_chooser(
+    selection: ActiveTab<&EntrySelection>,
+    mime_apps: Res<MimeApps>,
+    mut open_with: ResMut<OpenWith>,
+) {
+    let Some(selection) = selection.get() else {
+        return;
+    };
+    let paths = selection
+        .targets()
+        .into_iter()
+        .map(Path::to_owned)
+        .collect_vec();
+    let Some(path) = selection
+        .focused_path()
+        .or(paths.first().map(PathBuf::as_path))
+    else {
+        return;
+    };
+    *open_with = OpenWith {
+        open: true,
+        mime_type: mime_apps.mime_type(path),
+        paths,
+    };
+}
+
+fn close_on_escape(keys: Res<ButtonInput<KeyCode>>, mut open_with: ResMut<OpenWith>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        open_with.open = false;
+    }
+}
+
+pub(crate) fn open_with_plugin(app: &mut App) {
+    app.init_resource::<OpenWith>()
+        .add_command(RegisteredCommand::new("Open with", |commands| {
+            commands.run_system_cached(open_chooser);
+        }))
+        .add_systems(
+            Update,
+            (
+                close_on_escape.run_if(|open_with: Res<OpenWith>| open_with.open),
+                broadcast_fn(UpdateOpenWith)
+                    .run_if(resource_changed::<OpenWith>.or(resource_changed::<MimeApps>)),
+            )
+                .chain(),
+        );
+}
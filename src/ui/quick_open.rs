@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use bevy::time::common_conditions::on_timer;
+
+use crate::bridge::DirectoryChangeRequest;
+use crate::command_registry::{AddCommandExt, KeyBinding, RegisteredCommand};
+use crate::fs::EntryType;
+use crate::prelude::*;
+use crate::search::file_index::{FileIndex, fuzzy_score, index_current_directory};
+use crate::traits::WithUiIcon;
+use crate::ui::text_input::{FocusedTextInput, TextInput, TextInputCancelled, TextInputChanged,
+                            TextInputSubmitted, setup_text_input};
+use crate::ui::ui_events::UpdateQuickOpen;
+use crate::ui::{ExplorerCommand, broadcast_fn};
+
+/// only the best matches are listed
+const MAX_SHOWN_MATCHES: usize = 20;
+
+#[derive(Debug, Default, Resource)]
+struct QuickOpen {
+    open: bool,
+    query: String,
+    /// index into [QuickOpenMatches]
+    selected: usize,
+}
+
+/// Indices into the [FileIndex], best match first
+#[derive(Debug, Default, Deref, DerefMut, Resource)]
+struct QuickOpenMatches(Vec<usize>);
+
+#[derive(Component, Debug)]
+struct QuickOpenInput;
+
+fn close(quick_open: &mut QuickOpen, focused: &mut FocusedTextInput) {
+    quick_open.open = false;
+    **focused = None;
+}
+
+fn open_selected(
+    mut commands: Commands,
+    mut quick_open: ResMut<QuickOpen>,
+    mut focused: ResMut<FocusedTextInput>,
+    matches: Res<QuickOpenMatches>,
+    index: Res<FileIndex>,
+) {
+    let Some(indexed) = matches
+        .get(quick_open.selected)
+        .and_then(|i| index.paths.get(*i))
+    else {
+        return;
+    };
+    let path = indexed.entry.path().to_owned();
+    match indexed.entry.entry_type() {
+        EntryType::Directory | EntryType::Symlink => {
+            commands.send_event(DirectoryChangeRequest::from(path));
+        }
+        _ => {
+            commands
+                .react()
+                .broadcast(ExplorerCommand::SetPreview(Some(path)));
+        }
+    }
+    close(&mut quick_open, &mut focused);
+}
+
+pub fn setup_quick_open<'a>(popup: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    popup.update_on(
+        broadcast::<UpdateQuickOpen>(),
+        |id: TargetId,
+         quick_open: Res<QuickOpen>,
+         mut display_controls: Query<&mut DisplayControl>| {
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = if quick_open.open {
+                    DisplayControl::Show
+                } else {
+                    DisplayControl::Hide
+                };
+            }
+        },
+    );
+
+    let mut input = popup.get("input");
+    setup_text_input(&mut input, "go to file or directory");
+    input.insert(QuickOpenInput);
+    input.on_event::<TextInputChanged>().r(
+        |event: EntityEvent<TextInputChanged>, mut quick_open: ResMut<QuickOpen>| {
+            let (_, TextInputChanged(query)) = event.read();
+            quick_open.query = query.clone();
+            quick_open.selected = 0;
+        },
+    );
+    input.on_event::<TextInputSubmitted>().r(open_selected);
+    input.on_event::<TextInputCancelled>().r(
+        |mut quick_open: ResMut<QuickOpen>, mut focused: ResMut<FocusedTextInput>| {
+            close(&mut quick_open, &mut focused);
+        },
+    );
+
+    popup.get("results").update_on(
+        broadcast::<UpdateQuickOpen>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         quick_open: Res<QuickOpen>,
+         matches: Res<QuickOpenMatches>,
+         index: Res<FileIndex>| {
+            commands.entity(*id).despawn_related::<Children>();
+            for (i, indexed) in matches
+                .iter()
+                .filter_map(|i| index.paths.get(*i))
+                .enumerate()
+            {
+                commands.ui_builder(*id).spawn_scene(
+                    ("quick_open", "quick_open_item"),
+                    &mut scene_builder,
+                    |item| {
+                        if i == quick_open.selected {
+                            item.insert(BackgroundColor(Color::srgb(0.15, 0.39, 0.92)));
+                        }
+                        item.get("icon")
+                            .update_text(indexed.entry.entry_type().get_icon());
+                        item.get("path").update_text(indexed.relative.clone());
+                    },
+                );
+            }
+        },
+    );
+
+    popup.get("status").update_on(
+        broadcast::<UpdateQuickOpen>(),
+        |id: TargetId, mut commands: Commands, index: Res<FileIndex>| {
+            let indexing = if index.complete { "" } else { ", indexing..." };
+            commands
+                .ui_builder(*id)
+                .update_text(format!("{} paths{indexing}", index.paths.len()));
+        },
+    );
+}
+
+fn toggle_quick_open(
+    mut commands: Commands,
+    mut quick_open: ResMut<QuickOpen>,
+    mut focused: ResMut<FocusedTextInput>,
+    mut inputs: Query<(Entity, &mut TextInput), With<QuickOpenInput>>,
+) {
+    if quick_open.open {
+        close(&mut quick_open, &mut focused);
+        return;
+    }
+    let Ok((input, mut text_input)) = inputs.single_mut() else {
+        return;
+    };
+    // start from scratch every time
+    text_input.value.clear();
+    *quick_open = QuickOpen {
+        open: true,
+        ..default()
+    };
+    **focused = Some(input);
+    commands.run_system_cached(index_current_directory);
+}
+
+fn move_selection(
+    keys: Res<ButtonInput<KeyCode>>,
+    matches: Res<QuickOpenMatches>,
+    mut quick_open: ResMut<QuickOpen>,
+) {
+    if matches.is_empty() {
+        return;
+    }
+    let last = matches.len() - 1;
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        quick_open.selected = if quick_open.selected >= last {
+            0
+        } else {
+            quick_open.selected + 1
+        };
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        quick_open.selected = quick_open.selected.checked_sub(1).unwrap_or(last);
+    }
+}
+
+fn rank_matches(
+    quick_open: Res<QuickOpen>,
+    index: Res<FileIndex>,
+    mut matches: ResMut<QuickOpenMatches>,
+) {
+    let ranked = index
+        .paths
+        .iter()
+        .enumerate()
+        .filter_map(|(i, indexed)| Some((fuzzy_score(&quick_open.query, &indexed.relative)?, i)))
+        .k_largest(MAX_SHOWN_MATCHES)
+        .map(|(_, i)| i)
+        .collect_vec();
+    **matches = ranked;
+}
+
+pub(crate) fn quick_open_plugin(app: &mut App) {
+    app.init_resource::<QuickOpen>()
+        .init_resource::<QuickOpenMatches>()
+        .add_command(
+            RegisteredCommand::new("Quick open", |commands| {
+                commands.run_system_cached(toggle_quick_open);
+            })
+            .bind(KeyBinding::new(KeyCode::KeyP).ctrl()),
+        )
+        .add_systems(
+            Update,
+            (
+                move_selection.run_if(|quick_open: Res<QuickOpen>| quick_open.open),
+                rank_matches.run_if((|quick_open: Res<QuickOpen>| quick_open.open).and(
+                    resource_changed::<QuickOpen>.or(
+                        // the index grows while it is being built
+                        on_timer(Duration::from_millis(250)).and(resource_changed::<FileIndex>),
+                    ),
+                )),
+                broadcast_fn(UpdateQuickOpen)
+                    .run_if(resource_changed::<QuickOpen>.or(resource_changed::<QuickOpenMatches>)),
+            )
+                .chain(),
+        );
+}
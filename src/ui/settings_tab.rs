@@ -1,64 +1,137 @@
 use bevy::prelude::*;
+use bevy::window::{Monitor, PrimaryMonitor, PrimaryWindow};
 use bevy_cobweb::prelude::*;
 use bevy_cobweb_ui::prelude::*;
-use bevy_cobweb_ui::sickle::UpdateTextExt;
 
+use crate::notifications::Notification;
 use crate::resources::PanelLayout;
+use crate::settings::Settings;
+use crate::ui::broadcast_fn;
+use crate::ui::text_input::{TextInputSubmitted, setup_text_input};
+use crate::ui::ui_events::{UpdateSettings, UpdateWindowSize};
+use crate::window_settings::{DisplayMode, WindowSize, supported_sizes};
 
-pub fn init_settings_tab<'a>(settings_tab: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
-    let resolution_value = settings_tab.get("settings::resolution::header::value").id();
-    let mut shim = settings_tab.get("settings::resolution::options::view::shim");
-    for resolution in &["80x60", "800x600", "1024x768", "1920x1080"] {
-        shim.update(
-            move |id: TargetId, mut commands: Commands, mut scene_builder: SceneBuilder| {
+fn setup_resolution<'a>(resolution: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    resolution.get("header::value").update_on(
+        broadcast::<UpdateWindowSize>(),
+        |id: TargetId, mut commands: Commands, window: Single<&Window, With<PrimaryWindow>>| {
+            commands
+                .ui_builder(*id)
+                .update_text(WindowSize(window.physical_size()).to_string());
+        },
+    );
+
+    for mode in DisplayMode::ALL {
+        let key = mode.to_string().to_lowercase();
+        resolution
+            .get(format!("modes::{key}"))
+            .on_select(move |settings: ResMut<Settings>| {
+                settings
+                    .map_unchanged(|s| &mut s.window.mode)
+                    .set_if_neq(mode);
+            })
+            .update_on(
+                broadcast::<UpdateSettings>(),
+                select_if(move |settings| settings.window.mode == mode),
+            );
+    }
+
+    resolution.get("options::view::shim").update(
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         monitors: Query<&Monitor, With<PrimaryMonitor>>| {
+            for size in supported_sizes(&monitors) {
                 commands.ui_builder(*id).spawn_scene(
                     ("widgets", "list_option"),
                     &mut scene_builder,
                     |sh| {
-                        // set button text
-                        sh.update_text(*resolution);
-                        // set value label
-                        sh.on_select(move |mut commands: Commands| {
-                            commands
-                                .get_entity(resolution_value)?
-                                .update_text(*resolution);
-                            DONE
+                        sh.update_text(size.to_string());
+                        sh.on_select(move |settings: ResMut<Settings>| {
+                            settings
+                                .map_unchanged(|s| &mut s.window.size)
+                                .set_if_neq(Some(size));
                         });
+                        sh.update_on(
+                            broadcast::<UpdateSettings>(),
+                            select_if(move |settings| settings.window.size == Some(size)),
+                        );
                     },
                 );
-            },
-        );
+            }
+        },
+    );
+
+    let mut custom = resolution.get("custom");
+    setup_text_input(&mut custom, "custom size, like 1280x720");
+    custom.on_event::<TextInputSubmitted>().r(
+        |event: EntityEvent<TextInputSubmitted>,
+         mut settings: ResMut<Settings>,
+         mut notifications: EventWriter<Notification>| {
+            let (_, TextInputSubmitted(value)) = event.read();
+            match WindowSize::parse(value) {
+                Some(size) => settings.window.size = Some(size),
+                None => {
+                    notifications.write(Notification::warning(format!(
+                        "expected a size like 1280x720, got {value:?}"
+                    )));
+                }
+            }
+        },
+    );
+}
+
+/// Selects the option when the settings match it, also when the settings file was edited
+fn select_if(
+    matches: impl Fn(&Settings) -> bool + Send + Sync + 'static,
+) -> impl Fn(TargetId, Commands, Res<Settings>) + Send + Sync + 'static {
+    move |id: TargetId, mut commands: Commands, settings: Res<Settings>| {
+        if matches(&settings) {
+            commands.react().entity_event(*id, Select);
+        }
     }
+}
+
+pub fn init_settings_tab<'a>(settings_tab: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    setup_resolution(&mut settings_tab.get("settings::resolution"));
 
     settings_tab.edit("settings::layout", |layout_settings| {
-        let layout_value_label_id = layout_settings.get("header::value").id();
+        layout_settings.get("header::value").update_on(
+            broadcast::<UpdateSettings>(),
+            |id: TargetId, mut commands: Commands, settings: Res<Settings>| {
+                commands
+                    .ui_builder(*id)
+                    .update_text(settings.panel_layout.to_string());
+            },
+        );
         for layout in [
             PanelLayout::Automatic,
             PanelLayout::Horizontal,
             PanelLayout::Vertical,
         ] {
-            let name = layout.to_string();
-            let key = name.to_lowercase();
+            let key = layout.to_string().to_lowercase();
             layout_settings
                 .get(format!("options::{key}"))
-                .on_select(
-                    move |mut commands: Commands, mut panel_layout: ResMut<PanelLayout>| {
-                        // update resource
-                        *panel_layout = layout;
-                        // update label
-                        commands
-                            .ui_builder(layout_value_label_id)
-                            .update_text(&name);
-                    },
-                )
-                // select option that matches Res<PanelLayout>
-                .update(
-                    move |id: TargetId, mut commands: Commands, panel_layout: Res<PanelLayout>| {
-                        if layout == *panel_layout {
-                            commands.react().entity_event(*id, Select);
-                        }
-                    },
+                .on_select(move |settings: ResMut<Settings>| {
+                    settings
+                        .map_unchanged(|s| &mut s.panel_layout)
+                        .set_if_neq(layout);
+                })
+                .update_on(
+                    broadcast::<UpdateSettings>(),
+                    select_if(move |settings| settings.panel_layout == layout),
                 );
         }
     });
 }
+
+pub(crate) fn settings_tab_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            broadcast_fn(UpdateSettings).run_if(resource_changed::<Settings>),
+            broadcast_fn(UpdateWindowSize)
+                .run_if(any_match_filter::<(With<PrimaryWindow>, Changed<Window>)>),
+        ),
+    );
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use bevy::ui::UiSystem;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+use crate::ui::main_tab::DOUBLE_CLICK_TIME;
+
+const SPLITTER_THICKNESS: f32 = 6.0;
+
+/// Resizes the node before it by dragging, see `widgets::splitter`.
+/// Sizes are fractions of the parent, remembered per direction of the parent
+#[derive(Clone, Component, Debug, Default, PartialEq, Reflect)]
+struct Splitter {
+    name: String,
+    default: f32,
+    min: f32,
+    max: f32,
+}
+
+impl Splitter {
+    fn key(&self, direction: FlexDirection) -> String {
+        match direction {
+            FlexDirection::Column | FlexDirection::ColumnReverse => {
+                format!("{}.vertical", self.name)
+            }
+            _ => format!("{}.horizontal", self.name),
+        }
+    }
+}
+
+/// Dragged sizes by splitter and layout, splitters without one use their default.
+/// Saved with the session
+#[derive(Clone, Debug, Default, Deserialize, Resource, Serialize)]
+#[serde(transparent)]
+pub struct PaneSizes(HashMap<String, f32>);
+
+/// Sizes the node before each splitter and turns the splitter across the direction of their parent.
+/// Runs every frame since the direction changes with the layout, only differences are written
+fn apply_splitters(
+    splitters: Query<(Entity, &Splitter, &ChildOf)>,
+    children: Query<&Children>,
+    sizes: Res<PaneSizes>,
+    mut nodes: Query<&mut Node>,
+) {
+    for (e, splitter, child_of) in &splitters {
+        let Ok(direction) = nodes.get(child_of.parent()).map(|node| node.flex_direction) else {
+            continue;
+        };
+        let Some(before) = children.get(child_of.parent()).ok().and_then(|siblings| {
+            let index = siblings.iter().position(|sibling| sibling == e)?;
+            siblings.get(index.checked_sub(1)?).copied()
+        }) else {
+            continue;
+        };
+        let size = sizes
+            .0
+            .get(&splitter.key(direction))
+            .copied()
+            .unwrap_or(splitter.default);
+        if let Ok(mut node) = nodes.get_mut(before) {
+            let flex_basis = Val::Percent(size * 100.0);
+            if node.flex_basis != flex_basis {
+                node.flex_basis = flex_basis;
+                node.flex_grow = 0.0;
+                node.flex_shrink = 0.0;
+                // not held open by its content
+                node.min_width = Val::ZERO;
+                node.min_height = Val::ZERO;
+            }
+        }
+        let (width, height) = match direction {
+            FlexDirection::Column | FlexDirection::ColumnReverse => {
+                (Val::Auto, Val::Px(SPLITTER_THICKNESS))
+            }
+            _ => (Val::Px(SPLITTER_THICKNESS), Val::Auto),
+        };
+        if let Ok(mut node) = nodes.get_mut(e)
+            && (node.width != width || node.height != height)
+        {
+            node.width = width;
+            node.height = height;
+        }
+    }
+}
+
+fn on_splitter_drag(
+    trigger: Trigger<Pointer<Drag>>,
+    splitters: Query<(&Splitter, &ChildOf)>,
+    parents: Query<(&Node, &ComputedNode)>,
+    mut sizes: ResMut<PaneSizes>,
+) {
+    let Ok((splitter, child_of)) = splitters.get(trigger.target()) else {
+        return;
+    };
+    let Ok((node, computed)) = parents.get(child_of.parent()) else {
+        return;
+    };
+    let parent_size = computed.size() * computed.inverse_scale_factor();
+    let delta = trigger.event().delta;
+    let (delta, length) = match node.flex_direction {
+        FlexDirection::Column | FlexDirection::ColumnReverse => (delta.y, parent_size.y),
+        _ => (delta.x, parent_size.x),
+    };
+    if length <= 0.0 {
+        return;
+    }
+    let key = splitter.key(node.flex_direction);
+    let size = sizes.0.get(&key).copied().unwrap_or(splitter.default);
+    sizes.0.insert(
+        key,
+        (size + delta / length).clamp(splitter.min, splitter.max),
+    );
+}
+
+/// Double clicks go back to the default size
+fn on_splitter_click(
+    trigger: Trigger<Pointer<Click>>,
+    mut last_click: Local<Option<Instant>>,
+    splitters: Query<(&Splitter, &ChildOf)>,
+    nodes: Query<&Node>,
+    mut sizes: ResMut<PaneSizes>,
+) {
+    let Ok((splitter, child_of)) = splitters.get(trigger.target()) else {
+        return;
+    };
+    let now = Instant::now();
+    if last_click
+        .replace(now)
+        .is_some_and(|last| now - last < DOUBLE_CLICK_TIME)
+        && let Ok(node) = nodes.get(child_of.parent())
+    {
+        *last_click = None;
+        sizes.0.remove(&splitter.key(node.flex_direction));
+    }
+}
+
+pub(crate) fn splitter_plugin(app: &mut App) {
+    app.init_resource::<PaneSizes>()
+        .register_component_type::<Splitter>()
+        .add_observer(on_splitter_drag)
+        .add_observer(on_splitter_click)
+        .add_systems(PostUpdate, apply_splitters.before(UiSystem::Layout));
+}
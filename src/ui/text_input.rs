@@ -0,0 +1,170 @@
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+
+use crate::prelude::*;
+
+/// Single-line text field, edited with the keyboard while focused
+#[derive(Component, Debug)]
+pub struct TextInput {
+    pub value: String,
+    placeholder: String,
+    /// the entity showing the text
+    text: Entity,
+}
+
+/// The [TextInput] that receives keyboard input, if any
+#[derive(Debug, Default, Deref, DerefMut, Resource)]
+pub struct FocusedTextInput(Option<Entity>);
+
+/// Entity event: the value of a [TextInput] was edited
+#[derive(Clone, Debug)]
+pub struct TextInputChanged(pub String);
+
+/// Entity event: Enter was pressed in a [TextInput]
+#[derive(Clone, Debug)]
+pub struct TextInputSubmitted(pub String);
+
+/// Entity event: Escape was pressed in a [TextInput]
+#[derive(Clone, Copy, Debug)]
+pub struct TextInputCancelled;
+
+/// Turns a `widgets::text_input` scene into a [TextInput]
+pub fn setup_text_input<'a>(
+    input: &mut SceneHandle<'a, UiBuilder<'a, Entity>>,
+    placeholder: impl Into<String>,
+) {
+    assert!(input.get_entity("text").is_ok());
+    let text = input.get("text").id();
+    let id = input.id();
+    input
+        .insert(TextInput {
+            value: default(),
+            placeholder: placeholder.into(),
+            text,
+        })
+        .on_pressed(move |mut focused: ResMut<FocusedTextInput>| {
+            **focused = Some(id);
+        });
+}
+
+/// Clicking anywhere but a [TextInput] gives the keyboard back to the key bindings
+fn unfocus_on_click_elsewhere(
+    trigger: Trigger<Pointer<Click>>,
+    mut focused: ResMut<FocusedTextInput>,
+    inputs: Query<(), With<TextInput>>,
+    parents: Query<&ChildOf>,
+) {
+    // only once per click, not for every ancestor it bubbles up to
+    let target = trigger.event().target;
+    if trigger.target() != target || focused.is_none() {
+        return;
+    }
+    let in_input = std::iter::once(target)
+        .chain(parents.iter_ancestors(target))
+        .any(|entity| inputs.contains(entity));
+    if !in_input {
+        **focused = None;
+    }
+}
+
+fn edit_focused_text_input(
+    mut commands: Commands,
+    mut keyboard_input: EventReader<KeyboardInput>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut focused: ResMut<FocusedTextInput>,
+    mut inputs: Query<&mut TextInput>,
+) {
+    let Some(entity) = **focused else {
+        keyboard_input.clear();
+        return;
+    };
+    let Ok(mut input) = inputs.get_mut(entity) else {
+        // input was despawned
+        **focused = None;
+        return;
+    };
+    let modified = keys.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::AltLeft,
+        KeyCode::AltRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+    for event in keyboard_input.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let value = input.value.clone();
+                commands
+                    .react()
+                    .entity_event(entity, TextInputSubmitted(value));
+            }
+            Key::Escape => {
+                **focused = None;
+                commands.react().entity_event(entity, TextInputCancelled);
+                return;
+            }
+            Key::Backspace => {
+                if modified {
+                    // remove the last word
+                    let trimmed = input.value.trim_end();
+                    let len = trimmed
+                        .rfind(|c: char| c.is_whitespace() || c == '/')
+                        .map(|i| i + 1)
+                        .unwrap_or(0);
+                    input.value.truncate(len);
+                } else {
+                    input.value.pop();
+                }
+                let value = input.value.clone();
+                commands
+                    .react()
+                    .entity_event(entity, TextInputChanged(value));
+            }
+            _ => {
+                if !modified
+                    && let Some(text) = &event.text
+                    && !text.chars().any(char::is_control)
+                {
+                    input.value.push_str(text);
+                    let value = input.value.clone();
+                    commands
+                        .react()
+                        .entity_event(entity, TextInputChanged(value));
+                }
+            }
+        }
+    }
+}
+
+fn render_text_inputs(
+    focused: Res<FocusedTextInput>,
+    inputs: Query<(Entity, Ref<TextInput>)>,
+    mut text_editor: TextEditor,
+) {
+    for (entity, input) in &inputs {
+        if !(input.is_changed() || focused.is_changed()) {
+            continue;
+        }
+        let is_focused = **focused == Some(entity);
+        if is_focused {
+            write_text!(text_editor, input.text, "{}|", input.value);
+        } else if input.value.is_empty() {
+            write_text!(text_editor, input.text, "{}", input.placeholder);
+        } else {
+            write_text!(text_editor, input.text, "{}", input.value);
+        }
+    }
+}
+
+pub(crate) fn text_input_plugin(app: &mut App) {
+    app.init_resource::<FocusedTextInput>()
+        .add_observer(unfocus_on_click_elsewhere)
+        .add_systems(
+            Update,
+            (edit_focused_text_input, render_text_inputs).chain(),
+        );
+}
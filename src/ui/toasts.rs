@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use crate::notifications::{Notification, NotificationLog};
+use crate::prelude::*;
+use crate::traits::PathChecksExt;
+use crate::ui::broadcast_fn;
+use crate::ui::ui_events::{ToggleNotificationLog, UpdateNotificationLog};
+
+const TOAST_LIFETIME: Duration = Duration::from_secs(5);
+/// older toasts are dropped early when more than this are shown
+const MAX_VISIBLE_TOASTS: usize = 5;
+
+#[derive(Component, Debug)]
+struct ToastStack;
+
+#[derive(Component, Debug, Deref, DerefMut)]
+struct Toast(Timer);
+
+pub fn setup_toast_stack<'a>(toasts: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    assert!(toasts.path_ends_with(&["toasts"]));
+    toasts.insert(ToastStack);
+}
+
+pub fn setup_notification_log<'a>(log: &mut SceneHandle<'a, UiBuilder<'a, Entity>>) {
+    assert!(log.path_ends_with(&["notification_log"]));
+
+    log.reactor(
+        broadcast::<ToggleNotificationLog>(),
+        |id: TargetId, mut display_controls: Query<&mut DisplayControl>| {
+            if let Ok(mut display_control) = display_controls.get_mut(*id) {
+                *display_control = match *display_control {
+                    DisplayControl::Show => DisplayControl::Hide,
+                    DisplayControl::Hide => DisplayControl::Show,
+                };
+            }
+        },
+    );
+
+    log.get("header::clear_button")
+        .on_pressed(|mut log: ResMut<NotificationLog>| log.clear());
+
+    log.get("entries::view::shim").update_on(
+        broadcast::<UpdateNotificationLog>(),
+        |id: TargetId,
+         mut commands: Commands,
+         mut scene_builder: SceneBuilder,
+         log: Res<NotificationLog>| {
+            commands.entity(*id).despawn_related::<Children>();
+            // newest first
+            for notification in log.iter().rev() {
+                commands.ui_builder(*id).spawn_scene(
+                    ("notifications", "log_entry"),
+                    &mut scene_builder,
+                    |entry| {
+                        entry.get("time").update_text(notification.time_label());
+                        entry
+                            .get("severity")
+                            .insert(TextColor(notification.severity.color()))
+                            .update_text(notification.severity.to_string());
+                        entry.get("text").update_text(notification.message.clone());
+                    },
+                );
+            }
+        },
+    );
+}
+
+fn spawn_toasts(
+    mut commands: Commands,
+    mut scene_builder: SceneBuilder,
+    mut notifications: EventReader<Notification>,
+    stack: Query<Entity, With<ToastStack>>,
+) {
+    let Ok(stack) = stack.single() else {
+        // ui is being rebuilt, the notification log still has everything
+        notifications.clear();
+        return;
+    };
+    for notification in notifications.read() {
+        commands.ui_builder(stack).spawn_scene(
+            ("notifications", "toast"),
+            &mut scene_builder,
+            |toast| {
+                toast.insert((
+                    Toast(Timer::new(TOAST_LIFETIME, TimerMode::Once)),
+                    BackgroundColor(notification.severity.color()),
+                ));
+                toast.get("time").update_text(notification.time_label());
+                toast.get("text").update_text(notification.message.clone());
+            },
+        );
+    }
+}
+
+fn expire_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut Toast)>,
+    stack: Query<&Children, With<ToastStack>>,
+) {
+    for (e, mut toast) in &mut toasts {
+        if toast.tick(time.delta()).finished() {
+            commands.entity(e).try_despawn();
+        }
+    }
+    if let Ok(children) = stack.single()
+        && children.len() > MAX_VISIBLE_TOASTS
+    {
+        for e in children.iter().take(children.len() - MAX_VISIBLE_TOASTS) {
+            commands.entity(e).try_despawn();
+        }
+    }
+}
+
+pub(crate) fn toasts_plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (
+            spawn_toasts.run_if(on_event::<Notification>),
+            expire_toasts.run_if(any_match_filter::<With<Toast>>),
+            broadcast_fn(UpdateNotificationLog).run_if(resource_changed::<NotificationLog>),
+        ),
+    );
+}
@@ -15,6 +15,64 @@ pub struct UpdateLocationText;
 #[derive(Clone, Copy, Debug, Default)]
 pub struct UpdateOverview;
 
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateNotificationLog;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ToggleNotificationLog;
+
+#[cfg(debug_assertions)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateCobwebWarnings;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateLogView;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateQuickOpen;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateCommandPalette;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateSelectionStatus;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateOpenWith;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateExplorerTabs;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateOtherPane;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdatePanelLayout;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateWindowSize;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateSettings;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateBookmarks;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateHistory;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateHistoryDropdown;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdatePlacesMenu;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateDirectoryTree;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateColumns;
+
 #[derive(Clone, Debug, Default)]
 pub struct LocationSelectionUpdated {
     pub before: String,
@@ -0,0 +1,132 @@
+use std::fmt;
+
+use bevy::window::{Monitor, PrimaryMonitor, PrimaryWindow, VideoModeSelection, WindowMode};
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+use crate::settings::Settings;
+
+/// offered when the monitors do not tell their video modes
+const FALLBACK_SIZES: [UVec2; 4] = [
+    UVec2::new(800, 600),
+    UVec2::new(1024, 768),
+    UVec2::new(1280, 720),
+    UVec2::new(1920, 1080),
+];
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Display, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [Self::Windowed, Self::Borderless, Self::Fullscreen];
+}
+
+/// Physical size of a window or video mode, saved like `1280x720`
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct WindowSize(pub UVec2);
+
+impl WindowSize {
+    /// `1280x720`
+    pub fn parse(s: &str) -> Option<Self> {
+        let (width, height) = s.trim().split_once(['x', 'X'])?;
+        let size = UVec2::new(width.trim().parse().ok()?, height.trim().parse().ok()?);
+        (size.min_element() > 0).then_some(Self(size))
+    }
+}
+
+impl fmt::Display for WindowSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.0.x, self.0.y)
+    }
+}
+
+impl TryFrom<String> for WindowSize {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s).ok_or_else(|| format!("expected a size like 1280x720, got {s:?}"))
+    }
+}
+
+impl From<WindowSize> for String {
+    fn from(size: WindowSize) -> Self {
+        size.to_string()
+    }
+}
+
+/// What the resolution setting asks of the primary window, applied whenever it changes
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    /// `None` keeps the size the window was created with
+    pub size: Option<WindowSize>,
+    pub mode: DisplayMode,
+}
+
+/// The sizes of the primary monitor's video modes, largest first
+pub fn supported_sizes(monitors: &Query<&Monitor, With<PrimaryMonitor>>) -> Vec<WindowSize> {
+    let sizes = monitors
+        .iter()
+        .flat_map(|monitor| &monitor.video_modes)
+        .map(|mode| mode.physical_size)
+        .unique()
+        .sorted_by_key(|size| std::cmp::Reverse((size.x, size.y)))
+        .map(WindowSize)
+        .collect_vec();
+    match sizes.is_empty() {
+        true => FALLBACK_SIZES.into_iter().rev().map(WindowSize).collect(),
+        false => sizes,
+    }
+}
+
+/// Only when the window settings differ from the last applied ones,
+/// other settings changing should not undo resizing the window by hand
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut applied: Local<Option<WindowSettings>>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+    monitors: Query<&Monitor, With<PrimaryMonitor>>,
+) {
+    let settings = settings.window;
+    if applied.replace(settings) == Some(settings) {
+        return;
+    }
+    if let Some(WindowSize(size)) = settings.size {
+        window.resolution.set_physical_resolution(size.x, size.y);
+    }
+    window.mode = match settings.mode {
+        DisplayMode::Windowed => WindowMode::Windowed,
+        DisplayMode::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Primary),
+        DisplayMode::Fullscreen => {
+            // the fastest video mode of the chosen size, or whatever the monitor uses
+            let video_mode = settings.size.and_then(|WindowSize(size)| {
+                monitors
+                    .iter()
+                    .flat_map(|monitor| &monitor.video_modes)
+                    .filter(|mode| mode.physical_size == size)
+                    .max_by_key(|mode| mode.refresh_rate_millihertz)
+            });
+            WindowMode::Fullscreen(
+                MonitorSelection::Primary,
+                video_mode.map_or(VideoModeSelection::Current, |mode| {
+                    VideoModeSelection::Specific(*mode)
+                }),
+            )
+        }
+    };
+}
+
+pub fn window_settings_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        apply_window_settings.run_if(resource_changed::<Settings>),
+    );
+}
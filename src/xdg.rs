@@ -0,0 +1,56 @@
+use std::env;
+use std::path::PathBuf;
+
+/// directory name used below the XDG base directories
+pub const APP_DIR: &str = "corvus";
+
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+/// an XDG base directory from the environment, or its default below `$HOME`
+fn base_dir(var: &str, default: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        // relative paths are invalid and should be ignored
+        .filter(|path| path.is_absolute())
+        .or_else(|| home_dir().map(|home| home.join(default)))
+}
+
+/// a list of XDG directories from the environment, or its default, in order of preference
+fn dir_list(var: &str, default: &str) -> Vec<PathBuf> {
+    let paths = env::var_os(var).filter(|paths| !paths.is_empty());
+    env::split_paths(paths.as_deref().unwrap_or(default.as_ref()))
+        .filter(|path| path.is_absolute())
+        .collect()
+}
+
+/// `$XDG_STATE_HOME/corvus`
+pub fn state_dir() -> Option<PathBuf> {
+    base_dir("XDG_STATE_HOME", ".local/state").map(|dir| dir.join(APP_DIR))
+}
+
+/// `$XDG_CONFIG_HOME/corvus`
+pub fn config_dir() -> Option<PathBuf> {
+    config_home().map(|dir| dir.join(APP_DIR))
+}
+
+/// `$XDG_CONFIG_HOME`, shared with other applications
+pub fn config_home() -> Option<PathBuf> {
+    base_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_CONFIG_DIRS`
+pub fn config_dirs() -> Vec<PathBuf> {
+    dir_list("XDG_CONFIG_DIRS", "/etc/xdg")
+}
+
+/// `$XDG_DATA_HOME`, shared with other applications
+pub fn data_home() -> Option<PathBuf> {
+    base_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// `$XDG_DATA_DIRS`
+pub fn data_dirs() -> Vec<PathBuf> {
+    dir_list("XDG_DATA_DIRS", "/usr/local/share:/usr/share")
+}